@@ -0,0 +1,246 @@
+use crate::errors::ScraperError;
+#[cfg(feature = "regex")]
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Remembers a SHA-256 of the last body seen for each URL, so unchanged pages can be skipped
+///
+/// Unlike the HTTP caches this works whatever the server's validators say, and with
+/// [`ChangeTracker::load`] it carries over between runs as a JSON object of URL to hex digest.
+///
+/// Parts of a page that change on every request can be left out of the hash with
+/// [`ChangeTracker::ignore_between`], or with [`ChangeTracker::ignore`] when the `regex` feature
+/// is enabled.
+#[derive(Debug, Default)]
+pub struct ChangeTracker {
+    hashes: BTreeMap<String, String>,
+    /// Literal start and end markers whose contents are removed before hashing
+    ignored_between: Vec<(String, String)>,
+    #[cfg(feature = "regex")]
+    ignored: Vec<Regex>,
+    path: Option<PathBuf>,
+}
+
+impl ChangeTracker {
+    /// A tracker that starts empty and isn't persisted
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the hashes saved at `path`, starting empty if the file doesn't exist yet
+    ///
+    /// [`ChangeTracker::save`] writes back to the same file.
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, ScraperError> {
+        let path = path.into();
+        let hashes = match fs::read(&path) {
+            Ok(json) => serde_json::from_slice(&json).map_err(io::Error::from)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let mut tracker = Self::new();
+        tracker.hashes = hashes;
+        tracker.path = Some(path);
+        Ok(tracker)
+    }
+
+    /// Remove whatever lies between `start` and the next `end` from bodies before hashing, e.g.
+    /// the value of a CSRF token input
+    ///
+    /// The markers themselves are kept. An empty `start`, or one with no `end` after it, removes
+    /// nothing.
+    pub fn ignore_between(mut self, start: &str, end: &str) -> Self {
+        self.ignored_between
+            .push((start.to_string(), end.to_string()));
+        self
+    }
+
+    /// Remove matches of `pattern` from bodies before hashing, e.g. CSRF tokens or timestamps
+    #[cfg(feature = "regex")]
+    pub fn ignore(mut self, pattern: &str) -> Result<Self, ScraperError> {
+        self.ignored.push(Regex::new(pattern)?);
+        Ok(self)
+    }
+
+    /// Hex SHA-256 of `body` with the ignored regions removed
+    pub fn hash(&self, body: &str) -> String {
+        let body = self
+            .ignored_between
+            .iter()
+            .fold(body.to_string(), |body, (start, end)| {
+                remove_between(&body, start, end)
+            });
+        #[cfg(feature = "regex")]
+        let body = self.ignored.iter().fold(body.to_string(), |body, pattern| {
+            pattern.replace_all(&body, "").into_owned()
+        });
+        Sha256::digest(body.as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// Whether `body` differs from the last body recorded for `url`
+    ///
+    /// A URL that hasn't been recorded counts as changed.
+    pub fn is_changed(&self, url: &str, body: &str) -> bool {
+        self.hashes.get(url) != Some(&self.hash(body))
+    }
+
+    /// Remember `body` as the last one seen for `url`
+    ///
+    /// Call this once the page has been handled, so a failure leaves it counted as changed.
+    pub fn record(&mut self, url: &str, body: &str) {
+        let hash = self.hash(body);
+        self.hashes.insert(url.to_string(), hash);
+    }
+
+    /// Write the hashes to the file given to [`ChangeTracker::load`]; a no-op without one
+    pub fn save(&self) -> Result<(), ScraperError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let json = serde_json::to_vec_pretty(&self.hashes).map_err(io::Error::from)?;
+        fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+/// `body` without the text between each `start` and the `end` following it
+fn remove_between(body: &str, start: &str, end: &str) -> String {
+    if start.is_empty() {
+        return body.to_string();
+    }
+    let mut kept = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(found) = rest.find(start) {
+        let inside = found + start.len();
+        let Some(length) = rest[inside..].find(end) else {
+            break;
+        };
+        kept.push_str(&rest[..inside]);
+        rest = &rest[inside + length..];
+        kept.push_str(&rest[..end.len()]);
+        rest = &rest[end.len()..];
+    }
+    kept.push_str(rest);
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "https://example.com/holidays";
+
+    #[test]
+    fn test_hash_is_sha256_hex() {
+        assert_eq!(
+            ChangeTracker::new().hash("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    /// Whether `body` is new, recording it like the pipeline does after a successful run
+    fn check(tracker: &mut ChangeTracker, url: &str, body: &str) -> bool {
+        let changed = tracker.is_changed(url, body);
+        tracker.record(url, body);
+        changed
+    }
+
+    #[test]
+    fn test_is_changed_detects_changes() {
+        let mut tracker = ChangeTracker::new();
+        assert!(check(&mut tracker, URL, "<table>2025</table>"));
+        assert!(!check(&mut tracker, URL, "<table>2025</table>"));
+        assert!(check(&mut tracker, URL, "<table>2026</table>"));
+        assert!(check(
+            &mut tracker,
+            "https://example.com/other",
+            "<table>2026</table>"
+        ));
+    }
+
+    #[test]
+    fn test_is_changed_until_recorded() {
+        let mut tracker = ChangeTracker::new();
+        assert!(tracker.is_changed(URL, "<table>2025</table>"));
+        assert!(tracker.is_changed(URL, "<table>2025</table>"));
+        tracker.record(URL, "<table>2025</table>");
+        assert!(!tracker.is_changed(URL, "<table>2025</table>"));
+    }
+
+    #[test]
+    fn test_is_changed_skips_text_between_markers() {
+        let mut tracker = ChangeTracker::new()
+            .ignore_between(r#"<input name="csrf" value=""#, r#"""#)
+            .ignore_between("<!-- generated ", " -->");
+        let page = |csrf: &str, time: &str, year: u16| {
+            format!(
+                r#"<input name="csrf" value="{}"><!-- generated {} --><table>{}</table>"#,
+                csrf, time, year
+            )
+        };
+
+        assert!(check(&mut tracker, URL, &page("a1", "09:00", 2025)));
+        assert!(!check(&mut tracker, URL, &page("b2", "10:00", 2025)));
+        assert!(check(&mut tracker, URL, &page("b2", "10:00", 2026)));
+    }
+
+    #[test]
+    fn test_remove_between() {
+        assert_eq!(remove_between("a[1]b[22]c", "[", "]"), "a[]b[]c");
+        assert_eq!(remove_between("a[1]b[2", "[", "]"), "a[]b[2");
+        assert_eq!(remove_between("same", "", "]"), "same");
+        assert_eq!(remove_between("x<<y>>z", "<<", ">>"), "x<<>>z");
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_is_changed_skips_ignored_regions() {
+        let mut tracker = ChangeTracker::new()
+            .ignore(r#"<input name="csrf" value="[^"]*">"#)
+            .unwrap()
+            .ignore(r"Generated at \d{2}:\d{2}")
+            .unwrap();
+        let page = |csrf: &str, time: &str, year: u16| {
+            format!(
+                r#"<input name="csrf" value="{}"><p>Generated at {}</p><table>{}</table>"#,
+                csrf, time, year
+            )
+        };
+
+        assert!(check(&mut tracker, URL, &page("a1", "09:00", 2025)));
+        assert!(!check(&mut tracker, URL, &page("b2", "10:00", 2025)));
+        assert!(check(&mut tracker, URL, &page("b2", "10:00", 2026)));
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hashes.json");
+
+        let mut tracker = ChangeTracker::load(&path).unwrap();
+        assert!(check(&mut tracker, URL, "<table></table>"));
+        tracker.save().unwrap();
+
+        let mut reloaded = ChangeTracker::load(&path).unwrap();
+        assert!(!check(&mut reloaded, URL, "<table></table>"));
+        assert!(check(&mut reloaded, URL, "<table>new</table>"));
+    }
+
+    #[test]
+    fn test_load_rejects_corrupt_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hashes.json");
+        fs::write(&path, "not json").unwrap();
+
+        assert!(matches!(
+            ChangeTracker::load(&path),
+            Err(ScraperError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,129 @@
+use async_trait::async_trait;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Source of time for everything that waits or measures, so tests can control it
+#[async_trait]
+pub trait Clock: Send + Sync {
+    /// Current wall-clock time
+    fn now_utc(&self) -> SystemTime;
+
+    /// Current monotonic time, used for measuring elapsed durations
+    fn now_instant(&self) -> Instant;
+
+    /// Wait for the given duration
+    async fn sleep(&self, duration: Duration);
+}
+
+/// The real clock backed by the system time and `tokio::time::sleep`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+#[async_trait]
+impl Clock for SystemClock {
+    fn now_utc(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn now_instant(&self) -> Instant {
+        Instant::now()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+pub use mock::MockClock;
+
+#[cfg(any(test, feature = "testing"))]
+mod mock {
+    use super::Clock;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant, SystemTime};
+
+    /// A clock that only moves when advanced; `sleep` advances it and returns immediately
+    #[derive(Debug)]
+    pub struct MockClock {
+        start_utc: SystemTime,
+        start_instant: Instant,
+        state: Mutex<MockClockState>,
+    }
+
+    #[derive(Debug, Default)]
+    struct MockClockState {
+        elapsed: Duration,
+        sleeps: Vec<Duration>,
+    }
+
+    impl MockClock {
+        /// Create a mock clock whose wall-clock time starts at `start_utc`
+        pub fn new(start_utc: SystemTime) -> Self {
+            Self {
+                start_utc,
+                start_instant: Instant::now(),
+                state: Mutex::new(MockClockState::default()),
+            }
+        }
+
+        /// Move the clock forward
+        pub fn advance(&self, duration: Duration) {
+            self.state.lock().unwrap().elapsed += duration;
+        }
+
+        /// Total time the clock has moved since it was created
+        pub fn elapsed(&self) -> Duration {
+            self.state.lock().unwrap().elapsed
+        }
+
+        /// Every duration passed to `sleep`, in call order
+        pub fn sleeps(&self) -> Vec<Duration> {
+            self.state.lock().unwrap().sleeps.clone()
+        }
+    }
+
+    impl Default for MockClock {
+        fn default() -> Self {
+            Self::new(SystemTime::UNIX_EPOCH)
+        }
+    }
+
+    #[async_trait]
+    impl Clock for MockClock {
+        fn now_utc(&self) -> SystemTime {
+            self.start_utc + self.elapsed()
+        }
+
+        fn now_instant(&self) -> Instant {
+            self.start_instant + self.elapsed()
+        }
+
+        async fn sleep(&self, duration: Duration) {
+            let mut state = self.state.lock().unwrap();
+            state.elapsed += duration;
+            state.sleeps.push(duration);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_clock_advances_on_sleep() {
+        let clock = MockClock::default();
+        let start = clock.now_instant();
+
+        clock.sleep(Duration::from_secs(30)).await;
+        clock.advance(Duration::from_secs(5));
+
+        assert_eq!(clock.now_instant() - start, Duration::from_secs(35));
+        assert_eq!(
+            clock.now_utc(),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(35)
+        );
+        assert_eq!(clock.sleeps(), vec![Duration::from_secs(30)]);
+    }
+}
@@ -1,14 +1,200 @@
+use crate::parse_report::ParseReport;
+use crate::scraper_client::Redirect;
+use reqwest::StatusCode;
+use std::fmt;
+use std::time::Duration;
 use thiserror::Error as ThisError;
 #[derive(ThisError, Debug)]
 pub enum ScraperError {
+    #[cfg(feature = "regex")]
     #[error("Regex error: {0}")]
     RegexError(#[from] regex::Error),
     #[error("Selector error: {0}")]
     SelectorError(String),
+    #[error("No year columns in the holiday table headers")]
+    NoYearColumns,
+    #[error("Parse error: {0}")]
+    ParseError(ParseReport),
     #[error("Fetch error: {0}")]
     FetchError(#[from] reqwest::Error),
     #[error("SqliteConnectionError: {0}")]
     SqliteConnectionError(#[from] rusqlite::Error),
+    #[error("Timed out while {phase} after {elapsed:?}")]
+    Timeout {
+        phase: TimeoutPhase,
+        elapsed: Duration,
+    },
+    #[error("Deadline exceeded after {attempts} attempts in {elapsed:?}")]
+    DeadlineExceeded { elapsed: Duration, attempts: u8 },
+    #[error("HTTP {status} from {url}")]
+    HttpStatus {
+        status: StatusCode,
+        url: String,
+        /// The first 200 characters of the response body
+        body_snippet: String,
+    },
+    #[error("Giving up after {attempts} attempts: {last_error}")]
+    RetriesExhausted {
+        attempts: u8,
+        last_error: Box<ScraperError>,
+    },
+    #[error("Response from {url} is larger than {limit} bytes")]
+    ResponseTooLarge { limit: u64, url: String },
+    #[error("Too many redirects: {}", list_redirects(.chain))]
+    TooManyRedirects { chain: Vec<Redirect> },
+    #[error("Expected {expected} from {url} but got {got}")]
+    UnexpectedContentType {
+        expected: String,
+        got: String,
+        url: String,
+    },
+    #[error("Disallowed by robots.txt: {0}")]
+    DisallowedByRobots(String),
+    #[error("Circuit open for host: {0}")]
+    CircuitOpen(String),
+    #[error("TLS error: {0}")]
+    Tls(String),
+    #[error("Invalid proxy: {0}")]
+    InvalidProxy(String),
+    #[error("Invalid header: {0}")]
+    InvalidHeader(String),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
     #[error("Error: {0}")]
     CustomError(String),
+    #[error("{} of {} operations failed:{}", .errors.len(), .succeeded + .errors.len(), list_failures(.errors))]
+    PartialFailure {
+        succeeded: usize,
+        errors: Vec<(String, ScraperError)>,
+    },
+}
+
+/// Which part of a request ran out of time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutPhase {
+    /// Establishing the connection, see [`crate::scraper_client::ScraperClientBuilder::connect_timeout`]
+    Connect,
+    /// The request as a whole, see [`crate::scraper_client::ScraperClientBuilder::request_timeout`]
+    Request,
+}
+
+impl fmt::Display for TimeoutPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeoutPhase::Connect => f.write_str("connecting"),
+            TimeoutPhase::Request => f.write_str("waiting for the response"),
+        }
+    }
+}
+
+impl ScraperError {
+    /// The HTTP status the failure came from, looking through `RetriesExhausted`
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ScraperError::HttpStatus { status, .. } => Some(*status),
+            ScraperError::RetriesExhausted { last_error, .. } => last_error.status(),
+            _ => None,
+        }
+    }
+
+    /// Process exit code: 2 when some operations still succeeded, 1 for any other failure
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ScraperError::PartialFailure { succeeded, .. } if *succeeded > 0 => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// Split labelled results into the successful values and, if any failed, a `PartialFailure`
+pub fn collect_partial<T>(
+    results: impl IntoIterator<Item = (String, Result<T, ScraperError>)>,
+) -> (Vec<T>, Option<ScraperError>) {
+    let mut values = Vec::new();
+    let mut errors = Vec::new();
+    for (label, result) in results {
+        match result {
+            Ok(value) => values.push(value),
+            Err(err) => errors.push((label, err)),
+        }
+    }
+
+    if errors.is_empty() {
+        return (values, None);
+    }
+    let failure = ScraperError::PartialFailure {
+        succeeded: values.len(),
+        errors,
+    };
+    (values, Some(failure))
+}
+
+fn list_redirects(chain: &[Redirect]) -> String {
+    chain
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+fn list_failures(errors: &[(String, ScraperError)]) -> String {
+    errors
+        .iter()
+        .map(|(label, err)| format!("\n  - {}: {}", label, err))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn three_source_run() -> Vec<(String, Result<u32, ScraperError>)> {
+        vec![
+            ("wa".to_string(), Ok(10)),
+            (
+                "nsw".to_string(),
+                Err(ScraperError::CustomError("timed out".to_string())),
+            ),
+            ("vic".to_string(), Ok(12)),
+        ]
+    }
+
+    #[test]
+    fn test_collect_partial_one_failure() {
+        let (values, failure) = collect_partial(three_source_run());
+        assert_eq!(values, vec![10, 12]);
+
+        let failure = failure.expect("Expected a partial failure");
+        match &failure {
+            ScraperError::PartialFailure { succeeded, errors } => {
+                assert_eq!(*succeeded, 2);
+                assert_eq!(errors.len(), 1);
+                assert_eq!(errors[0].0, "nsw");
+            }
+            other => panic!("Unexpected error: {:?}", other),
+        }
+        assert_eq!(failure.exit_code(), 2);
+        assert_eq!(
+            failure.to_string(),
+            "1 of 3 operations failed:\n  - nsw: Error: timed out"
+        );
+    }
+
+    #[test]
+    fn test_collect_partial_total_failure() {
+        let results = vec![(
+            "wa".to_string(),
+            Err::<u32, _>(ScraperError::CustomError("down".to_string())),
+        )];
+        let (values, failure) = collect_partial(results);
+        assert!(values.is_empty());
+        assert_eq!(failure.expect("Expected a failure").exit_code(), 1);
+    }
+
+    #[test]
+    fn test_collect_partial_all_succeed() {
+        let (values, failure) = collect_partial(vec![("wa".to_string(), Ok::<_, ScraperError>(1))]);
+        assert_eq!(values, vec![1]);
+        assert!(failure.is_none());
+    }
 }
@@ -0,0 +1,68 @@
+use crate::errors::ScraperError;
+use crate::holiday_date::DateStatus;
+use crate::holiday_processor::Holiday;
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::io::{self, Write};
+
+/// How exported CSV is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// Field separator, e.g. `b';'` or `b'\t'` for spreadsheet locales that use a decimal comma
+    pub delimiter: u8,
+    /// Start with a `year,name,date,iso_date,status` row
+    pub header: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            header: true,
+        }
+    }
+}
+
+/// Column names, in the order of the fields of [`Row`]
+const HEADER: [&str; 5] = ["year", "name", "date", "iso_date", "status"];
+
+/// One exported row
+#[derive(Debug, Serialize)]
+struct Row<'a> {
+    year: i32,
+    name: &'a str,
+    date: &'a str,
+    /// Empty when the date couldn't be parsed
+    iso_date: Option<NaiveDate>,
+    /// `parsed`, `unparsed` or `not_announced`
+    status: DateStatus,
+}
+
+/// Write one row per holiday, quoting fields only where the delimiter or quotes require it
+pub(crate) fn write_csv<W: Write>(
+    holidays: &[Holiday],
+    writer: W,
+    options: CsvOptions,
+) -> Result<(), ScraperError> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(options.delimiter)
+        // Written by hand so an empty export still has its header
+        .has_headers(false)
+        .from_writer(writer);
+    if options.header {
+        writer.write_record(HEADER).map_err(io::Error::from)?;
+    }
+    for holiday in holidays {
+        writer
+            .serialize(Row {
+                year: holiday.year,
+                name: &holiday.name,
+                date: &holiday.date,
+                iso_date: holiday.parsed_date,
+                status: holiday.status,
+            })
+            .map_err(io::Error::from)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
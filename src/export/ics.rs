@@ -0,0 +1,141 @@
+use crate::errors::ScraperError;
+use crate::holiday_date::DateStatus;
+use crate::holiday_processor::Holiday;
+use log::{info, warn};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::time::SystemTime;
+
+const PRODID: &str = "-//rust-assignment//Public holidays//EN";
+
+/// RFC 5545 limit on a content line, not counting the CRLF
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Write an iCalendar file with one all-day event per holiday that has a parsed date
+///
+/// `stamp` becomes every event's `DTSTAMP`. Holidays without a parsed date are logged and left out.
+pub(crate) fn write_ics<W: Write>(
+    holidays: &[Holiday],
+    stamp: SystemTime,
+    mut writer: W,
+) -> Result<(), ScraperError> {
+    let dtstamp = humantime::format_rfc3339_seconds(stamp)
+        .to_string()
+        .replace(['-', ':'], "");
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODID),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    for holiday in holidays {
+        let Some(date) = holiday.parsed_date else {
+            if holiday.status == DateStatus::NotAnnounced {
+                info!(
+                    "Leaving {} {} out of the calendar until its date is announced",
+                    holiday.year, holiday.name
+                );
+            } else {
+                warn!(
+                    "Leaving {} {} out of the calendar: {:?} isn't a date",
+                    holiday.year, holiday.name, holiday.date
+                );
+            }
+            continue;
+        };
+        // All-day events end on the next day, exclusive
+        let Some(end) = date.succ_opt() else {
+            warn!(
+                "Leaving {} out of the calendar: {} has no next day",
+                holiday.name, date
+            );
+            continue;
+        };
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}", uid(&holiday.name, &date.to_string())),
+            format!("DTSTAMP:{}", dtstamp),
+            format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")),
+            format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")),
+            format!("SUMMARY:{}", escape_text(&holiday.name)),
+            // Holidays shouldn't show as busy time
+            "TRANSP:TRANSPARENT".to_string(),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut ics = String::new();
+    for line in &lines {
+        fold_line(line, &mut ics);
+    }
+    writer.write_all(ics.as_bytes())?;
+    Ok(())
+}
+
+/// The same for a holiday on every export, so calendars update events instead of duplicating them
+fn uid(name: &str, iso_date: &str) -> String {
+    let digest = Sha256::digest(format!("{}\n{}", name, iso_date).as_bytes());
+    let hex: String = digest[..16]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("{}@rust-assignment", hex)
+}
+
+/// Escape a TEXT value (RFC 5545 section 3.3.11)
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Append `line` with a CRLF, folding it into 75-octet pieces without splitting a character
+fn fold_line(line: &str, out: &mut String) {
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            // The leading space counts towards the limit
+            octets = 1;
+        }
+        out.push(c);
+        octets += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold_line_keeps_characters_whole() {
+        let line = format!("SUMMARY:{}", "é".repeat(40));
+        let mut folded = String::new();
+        fold_line(&line, &mut folded);
+
+        let physical: Vec<&str> = folded.trim_end_matches("\r\n").split("\r\n").collect();
+        assert_eq!(physical.len(), 2);
+        assert!(physical.iter().all(|line| line.len() <= MAX_LINE_OCTETS));
+        assert!(physical[1].starts_with(' '));
+        assert_eq!(folded.replace("\r\n ", ""), format!("{}\r\n", line));
+    }
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!(
+            escape_text(r"Labour Day; Show Day, Perth\Fremantle"),
+            r"Labour Day\; Show Day\, Perth\\Fremantle"
+        );
+    }
+}
@@ -0,0 +1,49 @@
+use crate::errors::ScraperError;
+use crate::holiday_processor::{Holiday, Source};
+use serde::Serialize;
+use std::io::{self, Write};
+use std::time::{Duration, SystemTime};
+
+/// Layout of exported JSON
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonStyle {
+    /// Indented, one field per line
+    #[default]
+    Pretty,
+    /// Everything on one line
+    Compact,
+}
+
+/// The exported document; field names are part of the format and must not change
+#[derive(Debug, Serialize)]
+struct Envelope<'a> {
+    /// Final URL of the page, `null` when the HTML didn't come from a fetch
+    source_url: Option<&'a str>,
+    /// RFC 3339 time of the fetch, `null` when unknown
+    fetched_at: Option<String>,
+    holidays: &'a [Holiday],
+}
+
+/// Write `holidays` wrapped in an envelope describing where they came from
+pub(crate) fn write_json<W: Write>(
+    holidays: &[Holiday],
+    source: Option<&Source>,
+    writer: W,
+    style: JsonStyle,
+) -> Result<(), ScraperError> {
+    let envelope = Envelope {
+        source_url: source.map(|source| source.url.as_str()),
+        fetched_at: source.map(|source| {
+            let fetched_at =
+                SystemTime::UNIX_EPOCH + Duration::from_secs(source.fetched_at.max(0) as u64);
+            humantime::format_rfc3339(fetched_at).to_string()
+        }),
+        holidays,
+    };
+    match style {
+        JsonStyle::Pretty => serde_json::to_writer_pretty(writer, &envelope),
+        JsonStyle::Compact => serde_json::to_writer(writer, &envelope),
+    }
+    .map_err(io::Error::from)?;
+    Ok(())
+}
@@ -0,0 +1,11 @@
+//! Writing parsed holidays out in formats other tools can read
+
+mod csv;
+mod ics;
+mod json;
+
+pub(crate) use self::csv::write_csv;
+pub use self::csv::CsvOptions;
+pub(crate) use ics::write_ics;
+pub(crate) use json::write_json;
+pub use json::JsonStyle;
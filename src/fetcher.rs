@@ -0,0 +1,86 @@
+use crate::errors::ScraperError;
+use crate::scraper_client::{FetchResponse, ScraperClient};
+use async_trait::async_trait;
+
+/// Something that can fetch a page, so code using [`ScraperClient`] can be tested without a network
+#[async_trait]
+pub trait Fetcher: Send + Sync {
+    /// Fetch `url`, returning the body with the status, headers and final URL it came with
+    async fn fetch(&self, url: &str) -> Result<FetchResponse, ScraperError>;
+}
+
+#[async_trait]
+impl Fetcher for ScraperClient {
+    async fn fetch(&self, url: &str) -> Result<FetchResponse, ScraperError> {
+        ScraperClient::fetch(self, url).await
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+pub use static_fetcher::StaticFetcher;
+
+#[cfg(any(test, feature = "testing"))]
+mod static_fetcher {
+    use super::Fetcher;
+    use crate::errors::ScraperError;
+    use crate::scraper_client::{FetchResponse, Transfer};
+    use async_trait::async_trait;
+    use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+    use reqwest::{StatusCode, Url};
+    use std::collections::HashMap;
+    use std::time::{Duration, SystemTime};
+
+    /// Serves canned HTML keyed by URL; any other URL gets a 404
+    #[derive(Debug, Default, Clone)]
+    pub struct StaticFetcher {
+        pages: HashMap<String, String>,
+    }
+
+    impl StaticFetcher {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Serve `html` for requests to exactly `url`
+        pub fn with_page(mut self, url: &str, html: &str) -> Self {
+            self.pages.insert(url.to_string(), html.to_string());
+            self
+        }
+    }
+
+    #[async_trait]
+    impl Fetcher for StaticFetcher {
+        async fn fetch(&self, url: &str) -> Result<FetchResponse, ScraperError> {
+            let Some(html) = self.pages.get(url) else {
+                return Err(ScraperError::HttpStatus {
+                    status: StatusCode::NOT_FOUND,
+                    url: url.to_string(),
+                    body_snippet: String::new(),
+                });
+            };
+            let final_url = Url::parse(url)
+                .map_err(|e| ScraperError::CustomError(format!("{}: {}", url, e)))?;
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static("text/html; charset=utf-8"),
+            );
+            Ok(FetchResponse {
+                body: html.clone(),
+                status: 200,
+                headers,
+                final_url,
+                redirects: Vec::new(),
+                encoding: "UTF-8",
+                attempts: 1,
+                elapsed: Duration::ZERO,
+                fetched_at: SystemTime::now(),
+                transfer: Transfer {
+                    wire_bytes: html.len() as u64,
+                    decoded_bytes: html.len() as u64,
+                    content_encoding: None,
+                },
+            })
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// Cell texts saying a date hasn't been set yet, compared in lowercase without trailing markers
+const PLACEHOLDERS: [&str; 8] = [
+    "to be proclaimed",
+    "to be announced",
+    "to be confirmed",
+    "to be advised",
+    "not yet proclaimed",
+    "tba",
+    "tbc",
+    "tbd",
+];
+
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+/// Monday first, matching `Weekday::num_days_from_monday`
+const WEEKDAYS: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+/// What a holiday's date cell turned out to hold
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DateStatus {
+    /// A real date, available as `Holiday::parsed_date`
+    Parsed,
+    /// Text that isn't a date or a known placeholder, kept only as written
+    #[default]
+    Unparsed,
+    /// A placeholder like "To be proclaimed", "TBA" or a dash: the date hasn't been set yet
+    NotAnnounced,
+}
+
+impl DateStatus {
+    /// The name used in exports and the database, e.g. `not_announced`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DateStatus::Parsed => "parsed",
+            DateStatus::Unparsed => "unparsed",
+            DateStatus::NotAnnounced => "not_announced",
+        }
+    }
+
+    /// The status named by [`DateStatus::as_str`]
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            DateStatus::Parsed,
+            DateStatus::Unparsed,
+            DateStatus::NotAnnounced,
+        ]
+        .into_iter()
+        .find(|status| status.as_str() == name)
+    }
+}
+
+/// Whether a date cell is a placeholder for a date that hasn't been set, like "To be proclaimed",
+/// "TBC" or a dash, ignoring case and footnote markers
+pub fn is_not_announced(text: &str) -> bool {
+    let text = text.trim();
+    if !text.is_empty()
+        && text
+            .chars()
+            .all(|c| matches!(c, '-' | '\u{2013}' | '\u{2014}'))
+    {
+        return true;
+    }
+    let text = text
+        .trim_end_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase();
+    let text = text.strip_prefix("date ").unwrap_or(&text);
+    PLACEHOLDERS.contains(&text)
+}
+
+/// Parse a date cell such as "Monday 1 January", "Mon 26 Jan" or "January 1" in `year`
+///
+/// Names may be abbreviated to three or more letters and footnote markers like "*" are ignored.
+/// A weekday that doesn't fall on the date is an error, since it usually means the cell was
+/// paired with the wrong year column.
+pub fn parse_holiday_date(text: &str, year: i32) -> Result<NaiveDate, String> {
+    let mut day = None;
+    let mut month = None;
+    let mut weekday = None;
+    for word in text.split(|c: char| c.is_whitespace() || c == ',') {
+        let word = word
+            .trim_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase();
+        if word.is_empty() {
+            continue;
+        }
+        if let Some(number) = day_number(&word) {
+            if day.replace(number).is_some() {
+                return Err("more than one day number".to_string());
+            }
+        } else if let Some(index) = find_name(&MONTHS, &word) {
+            if month.replace(index as u32 + 1).is_some() {
+                return Err("more than one month".to_string());
+            }
+        } else if let Some(index) = find_name(&WEEKDAYS, &word) {
+            weekday = Some(index);
+        } else {
+            return Err(format!("unexpected word {:?}", word));
+        }
+    }
+
+    let (Some(day), Some(month)) = (day, month) else {
+        return Err("no day and month".to_string());
+    };
+    let date = NaiveDate::from_ymd_opt(year, month, day).ok_or_else(|| {
+        format!(
+            "{} has no day {}",
+            capitalise(MONTHS[month as usize - 1]),
+            day
+        )
+    })?;
+    let actual = date.weekday().num_days_from_monday() as usize;
+    match weekday {
+        Some(weekday) if weekday != actual => Err(format!(
+            "{} is a {}, not a {}",
+            date,
+            capitalise(WEEKDAYS[actual]),
+            capitalise(WEEKDAYS[weekday])
+        )),
+        _ => Ok(date),
+    }
+}
+
+/// "1", "01" or an ordinal like "26th"
+fn day_number(word: &str) -> Option<u32> {
+    let digits = ["st", "nd", "rd", "th"]
+        .iter()
+        .find_map(|suffix| word.strip_suffix(suffix))
+        .unwrap_or(word);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// Index of the name `word` spells out or abbreviates to at least three letters
+fn find_name(names: &[&str], word: &str) -> Option<usize> {
+    if word.len() < 3 {
+        return None;
+    }
+    names.iter().position(|name| name.starts_with(word))
+}
+
+fn capitalise(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_parse_holiday_date_formats() {
+        let cases = [
+            ("Monday 1 January", 2024, date(2024, 1, 1)),
+            ("1 January", 2025, date(2025, 1, 1)),
+            ("Mon 26 Jan", 2026, date(2026, 1, 26)),
+            ("Friday 29 March*", 2024, date(2024, 3, 29)),
+            ("Monday 3 June #", 2024, date(2024, 6, 3)),
+            ("Tuesday, 26 December", 2023, date(2023, 12, 26)),
+            ("Monday 23 Sept", 2024, date(2024, 9, 23)),
+            ("December 25", 2023, date(2023, 12, 25)),
+            ("Thurs 25th April", 2024, date(2024, 4, 25)),
+        ];
+        for (text, year, expected) in cases {
+            assert_eq!(parse_holiday_date(text, year), Ok(expected), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_parse_holiday_date_rejects_garbage() {
+        for text in [
+            "",
+            "TBA",
+            "To be proclaimed",
+            "1 2 January",
+            "March April 3",
+        ] {
+            assert!(parse_holiday_date(text, 2024).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_is_not_announced() {
+        for text in [
+            "To be proclaimed",
+            "to be proclaimed*",
+            "Date to be proclaimed",
+            "TBA",
+            "tbc",
+            "TBD#",
+            "To be announced",
+            "-",
+            "\u{2013}",
+            " \u{2014} ",
+        ] {
+            assert!(is_not_announced(text), "{:?}", text);
+        }
+        for text in ["Monday 1 January", "", "TBA 2025", "Show day", "--x"] {
+            assert!(!is_not_announced(text), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_date_status_names() {
+        for status in [
+            DateStatus::Parsed,
+            DateStatus::Unparsed,
+            DateStatus::NotAnnounced,
+        ] {
+            assert_eq!(DateStatus::from_name(status.as_str()), Some(status));
+            assert_eq!(
+                serde_json::to_value(status).unwrap(),
+                serde_json::Value::from(status.as_str())
+            );
+        }
+        assert_eq!(DateStatus::from_name("later"), None);
+    }
+
+    #[test]
+    fn test_parse_holiday_date_impossible_dates() {
+        assert_eq!(
+            parse_holiday_date("30 February", 2024),
+            Err("February has no day 30".to_string())
+        );
+        assert_eq!(
+            parse_holiday_date("Sunday 1 January", 2024),
+            Err("2024-01-01 is a Monday, not a Sunday".to_string())
+        );
+    }
+}
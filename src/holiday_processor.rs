@@ -1,84 +1,430 @@
 use crate::errors::ScraperError;
+use crate::export::{self, CsvOptions, JsonStyle};
+use crate::holiday_date::{is_not_announced, parse_holiday_date, DateStatus};
+use crate::normalize::{cell_text, collapse_whitespace};
+pub use crate::parse_report::{ParseReport, ParseWarning, WarningReason};
+use crate::scraper_client::FetchResponse;
+pub use crate::selectors::SelectorConfig;
+use crate::selectors::Selectors;
+use chrono::NaiveDate;
 use log::{info, warn};
-use regex::Regex;
-use rusqlite::{params, Connection};
-use scraper::{Html, Selector};
+use rusqlite::{params, Connection, DropBehavior, OptionalExtension};
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::Write;
+use std::ops::RangeInclusive;
+use std::time::{Duration, SystemTime};
 
-#[derive(Debug)]
-struct Holiday {
-    year: String,
-    date: String,
-    name: String,
+/// Years outside this range are treated as parsing mistakes rather than data
+const YEAR_RANGE: RangeInclusive<i32> = 1900..=2100;
+
+const CREATE_HOLIDAYS_TABLE: &str = "CREATE TABLE IF NOT EXISTS holidays (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    date TEXT NOT NULL,
+    year INTEGER NOT NULL,
+    year_text TEXT,
+    source_url TEXT,
+    fetched_at INTEGER,
+    parsed_date TEXT,
+    table_index INTEGER,
+    category TEXT,
+    status TEXT
+)";
+
+/// Where legacy rows whose TEXT year couldn't be converted are kept, so migrating loses nothing
+const UNMIGRATED_TABLE: &str = "holidays_legacy_unmigrated";
+
+/// Columns added after the first schema, with their types, for upgrading older tables
+const ADDED_COLUMNS: [(&str, &str); 6] = [
+    ("source_url", "TEXT"),
+    ("fetched_at", "INTEGER"),
+    ("parsed_date", "TEXT"),
+    ("table_index", "INTEGER"),
+    ("category", "TEXT"),
+    ("status", "TEXT"),
+];
+
+/// One holiday in one year column of the table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Holiday {
+    pub year: i32,
+    /// Raw header text, kept only when it differed from `year` (e.g. "2024*")
+    pub year_text: Option<String>,
+    /// The date cell as written on the page
+    pub date: String,
+    /// `date` resolved against `year`, if it could be parsed
+    pub parsed_date: Option<NaiveDate>,
+    /// Whether `date` was a date, a placeholder like "To be proclaimed" or neither
+    #[serde(default)]
+    pub status: DateStatus,
+    pub name: String,
+    /// Position of the table the holiday came from among the tables on the page, from 0
+    #[serde(default)]
+    pub table_index: usize,
+    /// The table's caption or heading, e.g. "Regional show days"
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+impl Holiday {
+    /// The cell text, followed by the ISO date when it was parsed
+    fn display_date(&self) -> String {
+        match (self.status, self.parsed_date) {
+            (DateStatus::NotAnnounced, _) => format!("{} (not announced)", self.date),
+            (_, Some(parsed)) => format!("{} ({})", self.date, parsed),
+            (_, None) => self.date.clone(),
+        }
+    }
+
+    /// What makes two entries for the same holiday and year the same day: the parsed date, any
+    /// placeholder, or else the cell text
+    fn date_key(&self) -> (DateStatus, Option<NaiveDate>, &str) {
+        match self.status {
+            DateStatus::Unparsed => (self.status, None, &self.date),
+            _ => (self.status, self.parsed_date, ""),
+        }
+    }
+}
+
+/// A holiday listed with more than one date in the same year, see [`HolidayProcessor::conflicts`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HolidayConflict {
+    pub year: i32,
+    pub name: String,
+    /// The differing date cells, in the order they appeared
+    pub dates: Vec<String>,
 }
 
 pub struct HolidayProcessor {
     raw_html: String,
     holidays: Vec<Holiday>,
+    source: Option<Source>,
+    selectors: SelectorConfig,
+    first_table_only: bool,
+    report: ParseReport,
+}
+
+/// Where the HTML came from, saved alongside each holiday
+#[derive(Debug)]
+pub(crate) struct Source {
+    pub(crate) url: String,
+    /// Seconds since the Unix epoch
+    pub(crate) fetched_at: i64,
 }
 
 impl HolidayProcessor {
     pub fn new(html: String) -> Self {
+        Self::with_selectors(html, SelectorConfig::default())
+    }
+
+    /// Process a page whose table is laid out differently from the WA one
+    pub fn with_selectors(html: String, selectors: SelectorConfig) -> Self {
         Self {
             raw_html: html,
             holidays: vec![],
+            source: None,
+            selectors,
+            first_table_only: false,
+            report: ParseReport::default(),
+        }
+    }
+
+    /// Only read the first matching table, as before pages with several tables were supported
+    pub fn first_table_only(mut self, enabled: bool) -> Self {
+        self.first_table_only = enabled;
+        self
+    }
+
+    /// Process a fetched page, recording its final URL and fetch time with the saved holidays
+    pub fn from_response(response: &FetchResponse) -> Self {
+        let fetched_at = response
+            .fetched_at
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_secs() as i64);
+        Self {
+            source: Some(Source {
+                url: response.final_url.to_string(),
+                fetched_at,
+            }),
+            ..Self::new(response.body.clone())
         }
     }
 
+    /// Parse the stored HTML into holidays, one per holiday and year column
+    ///
+    /// Header columns that aren't a year, like "Notes", are skipped along with their date cells.
+    /// Fails with [`ScraperError::NoYearColumns`] if there are tables but none has a year column.
+    /// Rows that were skipped or only partly read are listed in [`HolidayProcessor::report`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "parse",
+            skip(self),
+            fields(
+                url = self.source.as_ref().map(|source| source.url.as_str()),
+                bytes = self.raw_html.len(),
+                holidays = tracing::field::Empty
+            )
+        )
+    )]
     pub async fn run(&mut self) -> Result<(), ScraperError> {
         let document: Html = Html::parse_document(&self.raw_html);
 
-        let year_selector = Selector::parse("thead th")
-            .map_err(|err| ScraperError::SelectorError(err.to_string()))?;
-        let row_selector = Selector::parse("tbody tr")
+        let selectors = self.selectors.parse()?;
+        let cell_selector = Selector::parse("th, td")
             .map_err(|err| ScraperError::SelectorError(err.to_string()))?;
-        let name_selector = Selector::parse("th strong")
-            .map_err(|err| ScraperError::SelectorError(err.to_string()))?;
-        let date_selector =
-            Selector::parse("td").map_err(|err| ScraperError::SelectorError(err.to_string()))?;
+        let tables = document.select(&selectors.table);
+        let tables = tables.take(if self.first_table_only { 1 } else { usize::MAX });
+        self.report = ParseReport::default();
+
+        // Each table is read against its own year headers
+        let mut year_tables = 0;
+        let mut yearless_tables = 0;
+        for (table_index, table) in tables.enumerate() {
+            match parse_table(
+                table,
+                table_index,
+                &selectors,
+                &cell_selector,
+                &mut self.report,
+            ) {
+                Ok(holidays) => {
+                    year_tables += 1;
+                    self.holidays.extend(holidays);
+                }
+                Err(ScraperError::NoYearColumns) => {
+                    warn!("Skipping table {}: no year columns", table_index);
+                    yearless_tables += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        // Tables were found but none could be read, so the layout has probably changed
+        if year_tables == 0 && yearless_tables > 0 {
+            return Err(ScraperError::NoYearColumns);
+        }
+
+        self.parse_dates();
+        self.dedup();
 
-        let mut year_iter = document.select(&year_selector).skip(1); // Skip the empty first column for names
-        let mut years = Vec::new();
-        let re = Regex::new(r"\s+")?;
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("holidays", self.holidays.len());
+        Ok(())
+    }
 
-        // Extract all years from the <thead>
-        while let Some(year_element) = year_iter.next() {
-            let year_text = year_element.inner_html().trim().to_string();
-            years.push(year_text);
+    /// Like [`HolidayProcessor::run`], but fails with [`ScraperError::ParseError`] if any row gave
+    /// a warning
+    ///
+    /// The holidays that were read are kept either way, so they can be inspected.
+    pub async fn run_strict(&mut self) -> Result<(), ScraperError> {
+        self.run().await?;
+        if self.report.warnings.is_empty() {
+            Ok(())
+        } else {
+            Err(ScraperError::ParseError(self.report.clone()))
         }
+    }
 
-        let mut row_iter = document.select(&row_selector);
-
-        // Iterate over the rows in the <tbody>
-        while let Some(row) = row_iter.next() {
-            if let Some(name_element) = row.select(&name_selector).next() {
-                let holiday_name = name_element
-                    .clone()
-                    .inner_html()
-                    .replace("<br>", " ")
-                    .replace("&amp;", "&")
-                    .replace("&nbsp;", " ");
-
-                let mut date_iter = row.select(&date_selector);
-                let mut year_iter = years.iter();
-
-                while let (Some(date_element), Some(year)) = (date_iter.next(), year_iter.next()) {
-                    let holiday_date = date_element
-                        .clone()
-                        .inner_html()
-                        .replace("<br>", " ")
-                        .replace("&amp;", "&")
-                        .replace("&nbsp;", " ");
-
-                    self.holidays.push(Holiday {
-                        year: year.clone(),
-                        date: re.replace_all(&holiday_date, " ").trim().to_string(),
-                        name: holiday_name.trim().to_string(),
-                    });
-                }
+    /// How the rows of the last [`HolidayProcessor::run`] were read
+    pub fn report(&self) -> &ParseReport {
+        &self.report
+    }
+
+    /// Remove repeated entries for the same year, name and date, keeping the first of each
+    ///
+    /// Called by [`HolidayProcessor::run`], so running twice or a page listing a holiday in two
+    /// tables doesn't save it twice. Returns how many were removed.
+    pub fn dedup(&mut self) -> usize {
+        let before = self.holidays.len();
+        let mut seen = HashSet::new();
+        let mut kept = Vec::with_capacity(before);
+        for holiday in self.holidays.drain(..) {
+            let (status, parsed, text) = holiday.date_key();
+            let key = (
+                holiday.year,
+                holiday.name.clone(),
+                status,
+                parsed,
+                text.to_string(),
+            );
+            if seen.insert(key) {
+                kept.push(holiday);
             }
         }
+        self.holidays = kept;
 
-        Ok(())
+        let removed = before - self.holidays.len();
+        if removed > 0 {
+            info!("Removed {} duplicate holidays", removed);
+        }
+        for conflict in self.conflicts() {
+            warn!(
+                "{} {} is listed with different dates: {}",
+                conflict.year,
+                conflict.name,
+                conflict.dates.join(", ")
+            );
+        }
+        removed
+    }
+
+    /// Holidays listed with different dates in the same year, where the page contradicts itself
+    ///
+    /// Every listed date is kept in [`HolidayProcessor::holidays`]; this only points them out.
+    pub fn conflicts(&self) -> Vec<HolidayConflict> {
+        let mut conflicts: Vec<HolidayConflict> = Vec::new();
+        for (index, holiday) in self.holidays.iter().enumerate() {
+            let earlier = &self.holidays[..index];
+            if earlier
+                .iter()
+                .any(|other| other.year == holiday.year && other.name == holiday.name)
+            {
+                // Already collected with the first entry
+                continue;
+            }
+            let dates: Vec<String> = self.holidays[index..]
+                .iter()
+                .filter(|other| other.year == holiday.year && other.name == holiday.name)
+                .map(|other| other.date.clone())
+                .collect();
+            if dates.len() > 1 {
+                conflicts.push(HolidayConflict {
+                    year: holiday.year,
+                    name: holiday.name.clone(),
+                    dates,
+                });
+            }
+        }
+        conflicts
+    }
+
+    /// Fill in `status` and `parsed_date` from each holiday's date cell and year, leaving `None`
+    /// where the cell isn't a date
+    fn parse_dates(&mut self) {
+        for holiday in &mut self.holidays {
+            if is_not_announced(&holiday.date) {
+                holiday.status = DateStatus::NotAnnounced;
+                holiday.parsed_date = None;
+                continue;
+            }
+            (holiday.status, holiday.parsed_date) =
+                match parse_holiday_date(&holiday.date, holiday.year) {
+                    Ok(date) => (DateStatus::Parsed, Some(date)),
+                    Err(reason) => {
+                        warn!(
+                            "Can't parse the {} date {:?} of {}: {}",
+                            holiday.year, holiday.date, holiday.name, reason
+                        );
+                        (DateStatus::Unparsed, None)
+                    }
+                };
+        }
+    }
+
+    /// Number of holidays found by [`HolidayProcessor::run`]
+    #[cfg(feature = "metrics")]
+    pub(crate) fn holidays_parsed(&self) -> usize {
+        self.holidays.len()
+    }
+
+    /// The holidays found by [`HolidayProcessor::run`], in table order
+    pub fn holidays(&self) -> &[Holiday] {
+        &self.holidays
+    }
+
+    /// Take the holidays found by [`HolidayProcessor::run`]
+    pub fn into_holidays(self) -> Vec<Holiday> {
+        self.holidays
+    }
+
+    /// The holidays as a JSON object with `source_url`, `fetched_at` and a `holidays` array
+    ///
+    /// `source_url` and `fetched_at` are `null` unless the processor was built with
+    /// [`HolidayProcessor::from_response`].
+    pub fn to_json(&self, style: JsonStyle) -> Result<String, ScraperError> {
+        let mut json = Vec::new();
+        self.write_json(&mut json, style)?;
+        Ok(String::from_utf8(json).expect("serde_json writes UTF-8"))
+    }
+
+    /// Write the output of [`HolidayProcessor::to_json`] to `writer`
+    pub fn write_json<W: Write>(&self, writer: W, style: JsonStyle) -> Result<(), ScraperError> {
+        export::write_json(&self.holidays, self.source.as_ref(), writer, style)
+    }
+
+    /// The holidays as CSV with `year`, `name`, `date`, `iso_date` and `status` columns
+    ///
+    /// `iso_date` is empty for dates that couldn't be parsed; `status` tells placeholders like
+    /// "To be proclaimed" (`not_announced`) apart from other text (`unparsed`).
+    pub fn to_csv(&self, options: CsvOptions) -> Result<String, ScraperError> {
+        let mut csv = Vec::new();
+        self.write_csv(&mut csv, options)?;
+        Ok(String::from_utf8(csv).expect("Holiday fields are UTF-8"))
+    }
+
+    /// Write the output of [`HolidayProcessor::to_csv`] to `writer`, e.g. a `File`
+    pub fn write_csv<W: Write>(&self, writer: W, options: CsvOptions) -> Result<(), ScraperError> {
+        export::write_csv(&self.holidays, writer, options)
+    }
+
+    /// The holidays as an iCalendar file with one all-day event each, for importing into
+    /// Outlook or Google Calendar
+    ///
+    /// Holidays without a parsed date are left out, see [`HolidayProcessor::undated`]. Events
+    /// are stamped with the fetch time, or the current time if it isn't known.
+    pub fn to_ics(&self) -> Result<String, ScraperError> {
+        let mut ics = Vec::new();
+        self.write_ics(&mut ics)?;
+        Ok(String::from_utf8(ics).expect("Holiday fields are UTF-8"))
+    }
+
+    /// Write the output of [`HolidayProcessor::to_ics`] to `writer`
+    pub fn write_ics<W: Write>(&self, writer: W) -> Result<(), ScraperError> {
+        let stamp = self.source.as_ref().map_or_else(SystemTime::now, |source| {
+            SystemTime::UNIX_EPOCH + Duration::from_secs(source.fetched_at.max(0) as u64)
+        });
+        export::write_ics(&self.holidays, stamp, writer)
+    }
+
+    /// Holidays in the `year` column, in table order
+    pub fn holidays_for_year(&self, year: i32) -> Vec<&Holiday> {
+        self.holidays
+            .iter()
+            .filter(|holiday| holiday.year == year)
+            .collect()
+    }
+
+    /// Holidays whose name contains `pattern`, ignoring case, e.g. "easter" for both Easter days
+    pub fn find_by_name(&self, pattern: &str) -> Vec<&Holiday> {
+        let pattern = pattern.to_lowercase();
+        self.holidays
+            .iter()
+            .filter(|holiday| holiday.name.to_lowercase().contains(&pattern))
+            .collect()
+    }
+
+    /// Holidays with a parsed date from `start` to `end`, both inclusive
+    ///
+    /// Undated holidays are never included.
+    pub fn holidays_between(&self, start: NaiveDate, end: NaiveDate) -> Vec<&Holiday> {
+        self.holidays
+            .iter()
+            .filter(|holiday| {
+                holiday
+                    .parsed_date
+                    .is_some_and(|date| (start..=end).contains(&date))
+            })
+            .collect()
+    }
+
+    /// Holidays whose date couldn't be parsed, which [`HolidayProcessor::to_ics`] leaves out
+    pub fn undated(&self) -> Vec<&Holiday> {
+        self.holidays
+            .iter()
+            .filter(|holiday| holiday.parsed_date.is_none())
+            .collect()
     }
 
     pub fn pretty_print(&self) {
@@ -90,39 +436,77 @@ impl HolidayProcessor {
         for holiday in &self.holidays {
             info!(
                 "Year: {}, Holiday: {}, Date: {}",
-                holiday.year, holiday.name, holiday.date
+                holiday.year,
+                holiday.name,
+                holiday.display_date()
             );
         }
         info!("--- End of Local Data ---\n");
     }
 
+    /// Save all parsed holidays in a single transaction
+    ///
+    /// Either every row is written or none is: a failed insert rolls back.
+    ///
+    /// Dropping the future can't leave a half-written save only because this never suspends: the
+    /// whole transaction runs on the first poll. Adding an `.await` inside would break that.
     pub async fn save_to_db(&self, conn: &Connection) -> Result<(), ScraperError> {
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS holidays (
-                id INTEGER PRIMARY KEY,
-                name TEXT NOT NULL,
-                date TEXT NOT NULL,
-                year TEXT NOT NULL
-            )",
-            [],
-        )?;
+        ensure_schema(conn)?;
 
+        let mut tx = conn.unchecked_transaction()?;
+        tx.set_drop_behavior(DropBehavior::Rollback);
+        let source_url = self.source.as_ref().map(|source| &source.url);
+        let fetched_at = self.source.as_ref().map(|source| source.fetched_at);
         for holiday in &self.holidays {
-            conn.execute(
-                "INSERT INTO holidays (name, date, year) VALUES (?1, ?2, ?3)",
-                params![holiday.name, holiday.date, holiday.year],
+            tx.execute(
+                "INSERT INTO holidays
+                 (name, date, year, year_text, source_url, fetched_at, parsed_date, table_index, category, status)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    holiday.name,
+                    holiday.date,
+                    holiday.year,
+                    holiday.year_text,
+                    source_url,
+                    fetched_at,
+                    holiday.parsed_date,
+                    holiday.table_index,
+                    holiday.category,
+                    holiday.status.as_str()
+                ],
             )?;
         }
+        tx.commit()?;
         Ok(())
     }
 
     pub async fn fetch_from_db(&self, conn: &Connection) -> Result<(), ScraperError> {
-        let mut stmt = conn.prepare("SELECT name, date, year FROM holidays")?;
+        ensure_schema(conn)?;
+
+        let mut stmt = conn.prepare(
+            "SELECT name, date, year, year_text, parsed_date, table_index, category, status FROM holidays",
+        )?;
         let holiday_iter = stmt.query_map([], |row| {
+            let parsed_date: Option<NaiveDate> = row.get(4)?;
+            let status: Option<String> = row.get(7)?;
+            // Rows saved before the status column only told dates from everything else
+            let status = status.as_deref().and_then(DateStatus::from_name).unwrap_or(
+                if parsed_date.is_some() {
+                    DateStatus::Parsed
+                } else {
+                    DateStatus::Unparsed
+                },
+            );
             Ok(Holiday {
                 name: row.get(0)?,
                 date: row.get(1)?,
                 year: row.get(2)?,
+                year_text: row.get(3)?,
+                parsed_date,
+                status,
+                // Rows saved before tables were told apart all came from the same one
+                table_index: row.get::<_, Option<usize>>(5)?.unwrap_or(0),
+                category: row.get(6)?,
             })
         })?;
 
@@ -131,7 +515,9 @@ impl HolidayProcessor {
             let holiday = holiday?;
             info!(
                 "Holiday: {}, Date: {}, Year: {}",
-                holiday.name, holiday.date, holiday.year
+                holiday.name,
+                holiday.display_date(),
+                holiday.year
             );
         }
         info!("--- End of Database Data ---\n");
@@ -140,9 +526,332 @@ impl HolidayProcessor {
     }
 }
 
+/// The holidays in one table, paired with that table's year columns
+fn parse_table(
+    table: ElementRef,
+    table_index: usize,
+    selectors: &Selectors,
+    cell_selector: &Selector,
+    report: &mut ParseReport,
+) -> Result<Vec<Holiday>, ScraperError> {
+    let category = table_category(&table);
+    let year_iter = table.select(&selectors.year_header).skip(1); // Skip the empty first column for names
+    let mut years: Vec<Option<(i32, Option<String>)>> = Vec::new();
+    let mut header_texts = Vec::new();
+    let mut ignored = Vec::new();
+
+    // Extract all years from the <thead>, keeping a slot for skipped columns so the
+    // date cells stay aligned
+    for year_element in year_iter {
+        let year_text = cell_text(&year_element);
+        header_texts.push(year_text.clone());
+        match parse_year(&year_text) {
+            Ok(year) if years.iter().flatten().any(|(seen, _)| *seen == year) => {
+                ignored.push(format!("{:?} (duplicate of {})", year_text, year));
+                years.push(None);
+            }
+            Ok(year) => years.push(Some((year, year_text_if_different(year, year_text)))),
+            Err(reason) => {
+                ignored.push(format!("{:?} ({})", year_text, reason));
+                years.push(None);
+            }
+        }
+    }
+    if !ignored.is_empty() {
+        warn!(
+            "Ignoring {} header columns of table {}: {}",
+            ignored.len(),
+            table_index,
+            ignored.join(", ")
+        );
+    }
+    if years.iter().all(Option::is_none) {
+        return Err(ScraperError::NoYearColumns);
+    }
+
+    let mut holidays = Vec::new();
+    // A name cell with `rowspan` also names the rows below it, which have no name cell
+    let mut spanning_name: Option<(String, usize)> = None;
+
+    // Iterate over the rows in the <tbody>
+    for (row_index, row) in table.select(&selectors.row).enumerate() {
+        report.rows_seen += 1;
+        if is_repeated_header(&row, cell_selector, &header_texts) {
+            warn!(
+                "Skipping row {} of table {}: it repeats the header row",
+                row_index, table_index
+            );
+            report.rows_skipped += 1;
+            continue;
+        }
+
+        let holiday_name = match row.select(&selectors.name_cell).next() {
+            Some(name_element) => {
+                let name = cell_text(&name_element);
+                let rows = cell_span(&name_element, &row, "rowspan");
+                spanning_name = (rows > 1).then(|| (name.clone(), rows - 1));
+                name
+            }
+            None => match &mut spanning_name {
+                Some((name, rows_left)) if *rows_left > 0 => {
+                    *rows_left -= 1;
+                    name.clone()
+                }
+                _ => {
+                    report.warn(table_index, row_index, &row, WarningReason::MissingName);
+                    report.rows_skipped += 1;
+                    continue;
+                }
+            },
+        };
+
+        let before = holidays.len();
+        let mut covered = 0;
+        let mut year_iter = years.iter();
+        for date_element in row.select(&selectors.date_cell) {
+            let holiday_date = cell_text(&date_element);
+
+            // A cell with `colspan` gives the same date to each year column it covers
+            let columns = cell_span(&date_element, &row, "colspan");
+            covered += columns;
+            for year in year_iter.by_ref().take(columns) {
+                let Some((year, year_text)) = year else {
+                    continue;
+                };
+                if holiday_date.is_empty() {
+                    let reason = WarningReason::EmptyDate { year: *year };
+                    report.warn(table_index, row_index, &row, reason);
+                }
+                holidays.push(Holiday {
+                    year: *year,
+                    year_text: year_text.clone(),
+                    date: holiday_date.clone(),
+                    parsed_date: None,
+                    status: DateStatus::default(),
+                    name: holiday_name.clone(),
+                    table_index,
+                    category: category.clone(),
+                });
+            }
+        }
+
+        if covered != years.len() {
+            let reason = WarningReason::CellCountMismatch {
+                cells: covered,
+                columns: years.len(),
+            };
+            report.warn(table_index, row_index, &row, reason);
+        }
+        if holidays.len() > before {
+            report.rows_parsed += 1;
+        } else {
+            report.rows_skipped += 1;
+        }
+    }
+    Ok(holidays)
+}
+
+/// The table's `<caption>`, or else the nearest heading before it, e.g. "Regional show days"
+fn table_category(table: &ElementRef) -> Option<String> {
+    let caption = table
+        .children()
+        .filter_map(ElementRef::wrap)
+        .find(|child| child.value().name() == "caption");
+    let heading = || {
+        // Headings before the table, or before whatever the table is nested in
+        std::iter::successors(Some(*table), |element| {
+            element.parent().and_then(ElementRef::wrap)
+        })
+        .find_map(|element| {
+            element
+                .prev_siblings()
+                .filter_map(ElementRef::wrap)
+                .find(|sibling| {
+                    matches!(
+                        sibling.value().name(),
+                        "h1" | "h2" | "h3" | "h4" | "h5" | "h6"
+                    )
+                })
+        })
+    };
+    let text: Vec<&str> = caption.or_else(heading)?.text().collect();
+    let text = collapse_whitespace(&text.join(" ")).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Parse a four-digit year header such as "2024" or "2024*", ignoring trailing footnote
+/// markers
+///
+/// A range like "2025/26" or "2025-2026" is read as its first year.
+fn parse_year(text: &str) -> Result<i32, String> {
+    let text = text.trim().trim_end_matches(|c: char| !c.is_alphanumeric());
+    let (first, last) = match text.split_once(['/', '-', '\u{2013}']) {
+        Some((first, last)) => (first.trim_end(), Some(last.trim_start())),
+        None => (text, None),
+    };
+    if !is_digits(first, 4) || last.is_some_and(|last| !is_digits(last, 2) && !is_digits(last, 4)) {
+        return Err("not a year".to_string());
+    }
+
+    let year: i32 = first.parse().map_err(|_| "not a year".to_string())?;
+    if let Some(last) = last {
+        let mut end: i32 = last.parse().map_err(|_| "not a year".to_string())?;
+        if last.len() == 2 {
+            // "1999/00" ends in the next century
+            end += year - year % 100;
+            if end <= year {
+                end += 100;
+            }
+        }
+        if end <= year {
+            return Err(format!("range ends before {}", year));
+        }
+    }
+    if !YEAR_RANGE.contains(&year) {
+        return Err(format!(
+            "year {} is outside {}-{}",
+            year,
+            YEAR_RANGE.start(),
+            YEAR_RANGE.end()
+        ));
+    }
+    Ok(year)
+}
+
+/// Whether `text` is exactly `len` ASCII digits
+fn is_digits(text: &str, len: usize) -> bool {
+    text.len() == len && text.chars().all(|c| c.is_ascii_digit())
+}
+
+/// The `colspan` or `rowspan` of the cell holding `element`, 1 if unset or invalid
+///
+/// `element` may be the cell itself or something inside it, like the `<strong>` in a name cell.
+fn cell_span(element: &ElementRef, row: &ElementRef, attribute: &str) -> usize {
+    std::iter::once(*element)
+        .chain(element.ancestors().filter_map(ElementRef::wrap))
+        .take_while(|ancestor| ancestor.id() != row.id())
+        .find(|ancestor| matches!(ancestor.value().name(), "td" | "th"))
+        .and_then(|cell| cell.value().attr(attribute))
+        .and_then(|span| span.trim().parse().ok())
+        .filter(|&span| span > 0)
+        .unwrap_or(1)
+}
+
+/// Whether a body row is a copy of the header, as happens when pages repeat it for printing
+fn is_repeated_header(row: &ElementRef, cell_selector: &Selector, header_texts: &[String]) -> bool {
+    let cells: Vec<String> = row
+        .select(cell_selector)
+        .skip(1) // The name column
+        .map(|cell| cell_text(&cell))
+        .filter(|text| !text.is_empty())
+        .collect();
+
+    !cells.is_empty()
+        && cells.iter().all(|text| {
+            header_texts.contains(text)
+                || (text.len() == 4 && text.chars().all(|c| c.is_ascii_digit()))
+        })
+}
+
+fn year_text_if_different(year: i32, text: String) -> Option<String> {
+    (text != year.to_string()).then_some(text)
+}
+
+/// Create the holidays table, migrating a legacy one first, so reads and writes see the current schema
+fn ensure_schema(conn: &Connection) -> Result<(), ScraperError> {
+    migrate_legacy_year_column(conn)?;
+    conn.execute(CREATE_HOLIDAYS_TABLE, [])?;
+    for (column, column_type) in ADDED_COLUMNS {
+        let exists: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('holidays') WHERE name = ?1",
+            [column],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            conn.execute(
+                &format!("ALTER TABLE holidays ADD COLUMN {} {}", column, column_type),
+                [],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Convert a holidays table created with a TEXT year column to the INTEGER schema
+///
+/// Rows whose year cannot be converted are logged and moved to [`UNMIGRATED_TABLE`] as they were.
+fn migrate_legacy_year_column(conn: &Connection) -> Result<(), ScraperError> {
+    let year_type: Option<String> = conn
+        .query_row(
+            "SELECT type FROM pragma_table_info('holidays') WHERE name = 'year'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if !year_type.is_some_and(|year_type| year_type.eq_ignore_ascii_case("TEXT")) {
+        return Ok(());
+    }
+
+    let mut tx = conn.unchecked_transaction()?;
+    tx.set_drop_behavior(DropBehavior::Rollback);
+    tx.execute("ALTER TABLE holidays RENAME TO holidays_legacy", [])?;
+    tx.execute(CREATE_HOLIDAYS_TABLE, [])?;
+
+    let mut migrated = 0;
+    let mut unmigrated = 0;
+    {
+        let mut stmt = tx.prepare("SELECT id, name, date, year FROM holidays_legacy")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let (id, name, date, year_text): (i64, String, String, String) =
+                (row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?);
+            match parse_year(&year_text) {
+                Ok(year) => {
+                    tx.execute(
+                        "INSERT INTO holidays (id, name, date, year, year_text) VALUES (?1, ?2, ?3, ?4, ?5)",
+                        params![id, name, date, year, year_text_if_different(year, year_text)],
+                    )?;
+                    migrated += 1;
+                }
+                Err(reason) => {
+                    warn!(
+                        "Keeping holiday {} ({}, {}) in {} instead of migrating it: year {:?} {}",
+                        id, name, date, UNMIGRATED_TABLE, year_text, reason
+                    );
+                    unmigrated += 1;
+                }
+            }
+        }
+    }
+
+    if unmigrated > 0 {
+        tx.execute(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS {} (id INTEGER, name TEXT, date TEXT, year TEXT)",
+                UNMIGRATED_TABLE
+            ),
+            [],
+        )?;
+        tx.execute(
+            &format!(
+                "INSERT INTO {} (id, name, date, year)
+                 SELECT id, name, date, year FROM holidays_legacy
+                 WHERE id NOT IN (SELECT id FROM holidays)",
+                UNMIGRATED_TABLE
+            ),
+            [],
+        )?;
+    }
+    tx.execute("DROP TABLE holidays_legacy", [])?;
+    tx.commit()?;
+    info!("Migrated {} holidays to the INTEGER year column", migrated);
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Duration;
 
     #[tokio::test]
     async fn test_holiday_processor_valid_html() {
@@ -168,23 +877,23 @@ mod tests {
         let mut processor = HolidayProcessor::new(html);
         processor.run().await.expect("Processor failed");
 
-        assert_eq!(processor.holidays.len(), 4);
+        assert_eq!(processor.holidays().len(), 4);
 
-        assert_eq!(processor.holidays[0].year, "2023");
-        assert_eq!(processor.holidays[0].name, "New Year's Day");
-        assert_eq!(processor.holidays[0].date, "January 1");
+        assert_eq!(processor.holidays()[0].year, 2023);
+        assert_eq!(processor.holidays()[0].name, "New Year's Day");
+        assert_eq!(processor.holidays()[0].date, "January 1");
 
-        assert_eq!(processor.holidays[1].year, "2024");
-        assert_eq!(processor.holidays[1].name, "New Year's Day");
-        assert_eq!(processor.holidays[1].date, "January 1");
+        assert_eq!(processor.holidays()[1].year, 2024);
+        assert_eq!(processor.holidays()[1].name, "New Year's Day");
+        assert_eq!(processor.holidays()[1].date, "January 1");
 
-        assert_eq!(processor.holidays[2].year, "2023");
-        assert_eq!(processor.holidays[2].name, "Christmas Day");
-        assert_eq!(processor.holidays[2].date, "December 25");
+        assert_eq!(processor.holidays()[2].year, 2023);
+        assert_eq!(processor.holidays()[2].name, "Christmas Day");
+        assert_eq!(processor.holidays()[2].date, "December 25");
 
-        assert_eq!(processor.holidays[3].year, "2024");
-        assert_eq!(processor.holidays[3].name, "Christmas Day");
-        assert_eq!(processor.holidays[3].date, "December 25");
+        assert_eq!(processor.holidays()[3].year, 2024);
+        assert_eq!(processor.holidays()[3].name, "Christmas Day");
+        assert_eq!(processor.holidays()[3].date, "December 25");
     }
 
     #[tokio::test]
@@ -195,7 +904,7 @@ mod tests {
         let result = processor.run().await;
         assert!(result.is_ok());
         assert_eq!(
-            processor.holidays.len(),
+            processor.holidays().len(),
             0,
             "No holidays should be parsed from empty HTML"
         );
@@ -209,9 +918,9 @@ mod tests {
         let mut processor = HolidayProcessor::new(html);
 
         let result = processor.run().await;
-        assert!(result.is_ok());
+        assert!(matches!(result, Err(ScraperError::NoYearColumns)));
         assert_eq!(
-            processor.holidays.len(),
+            processor.holidays().len(),
             0,
             "No holidays should be parsed from invalid HTML structure"
         );
@@ -233,6 +942,14 @@ mod tests {
                         <th><strong>Independence<br>Day</strong></th>
                         <td>July 4</td>
                     </tr>
+                    <tr>
+                        <th><strong>King&#8217;s <em>Birthday</em></strong></th>
+                        <td><a href="/kings-birthday"><span class="date">25</span> September</a></td>
+                    </tr>
+                    <tr>
+                        <th><strong>Christmas&#x20;&ndash;&#32;Boxing Day</strong></th>
+                        <td><span>25&#8211;26</span><br><small>December</small></td>
+                    </tr>
                 </tbody>
             </table>
         "#
@@ -241,15 +958,24 @@ mod tests {
         let mut processor = HolidayProcessor::new(html);
         processor.run().await.expect("Processor failed");
 
-        assert_eq!(processor.holidays.len(), 2);
+        assert_eq!(processor.holidays().len(), 4);
+
+        assert_eq!(processor.holidays()[0].year, 2023);
+        assert_eq!(processor.holidays()[0].name, "Labor & Workers' Day");
+        assert_eq!(processor.holidays()[0].date, "May 1");
 
-        assert_eq!(processor.holidays[0].year, "2023");
-        assert_eq!(processor.holidays[0].name, "Labor & Workers' Day");
-        assert_eq!(processor.holidays[0].date, "May 1");
+        assert_eq!(processor.holidays()[1].year, 2023);
+        assert_eq!(processor.holidays()[1].name, "Independence Day");
+        assert_eq!(processor.holidays()[1].date, "July 4");
 
-        assert_eq!(processor.holidays[1].year, "2023");
-        assert_eq!(processor.holidays[1].name, "Independence Day");
-        assert_eq!(processor.holidays[1].date, "July 4");
+        assert_eq!(processor.holidays()[2].name, "King\u{2019}s Birthday");
+        assert_eq!(processor.holidays()[2].date, "25 September");
+
+        assert_eq!(
+            processor.holidays()[3].name,
+            "Christmas \u{2013} Boxing Day"
+        );
+        assert_eq!(processor.holidays()[3].date, "25\u{2013}26 December");
     }
 
     #[tokio::test]
@@ -272,10 +998,1147 @@ mod tests {
         let mut processor = HolidayProcessor::new(html);
         processor.run().await.expect("Processor failed");
 
-        assert_eq!(processor.holidays.len(), 1);
+        assert_eq!(processor.holidays().len(), 1);
+
+        assert_eq!(processor.holidays()[0].year, 2023);
+        assert_eq!(processor.holidays()[0].name, "Holiday with No Date");
+        assert_eq!(processor.holidays()[0].date, "");
+    }
+
+    #[tokio::test]
+    async fn test_holiday_processor_year_headers() {
+        let html = r#"
+            <table>
+                <thead>
+                    <tr><th>Holiday</th><th>2024*</th><th>3024</th><th>TBA</th><th>2025</th></tr>
+                </thead>
+                <tbody>
+                    <tr>
+                        <th><strong>Australia Day</strong></th>
+                        <td>26 January</td><td>27 January</td><td>28 January</td><td>26 January</td>
+                    </tr>
+                </tbody>
+            </table>
+        "#
+        .to_string();
+
+        let mut processor = HolidayProcessor::new(html);
+        processor.run().await.expect("Processor failed");
+
+        assert_eq!(processor.holidays().len(), 2);
+
+        assert_eq!(processor.holidays()[0].year, 2024);
+        assert_eq!(processor.holidays()[0].year_text.as_deref(), Some("2024*"));
+        assert_eq!(processor.holidays()[0].date, "26 January");
+
+        assert_eq!(processor.holidays()[1].year, 2025);
+        assert_eq!(processor.holidays()[1].year_text, None);
+        assert_eq!(processor.holidays()[1].date, "26 January");
+    }
+
+    #[tokio::test]
+    async fn test_holiday_processor_skips_notes_column() {
+        let processor = processed(
+            r#"<table>
+                <thead><tr><th>Holiday</th><th>2025/26</th><th>Notes</th><th>2026</th></tr></thead>
+                <tbody>
+                    <tr>
+                        <th><strong>Labour Day</strong></th>
+                        <td>Monday 2 March</td><td>Second Monday in March</td><td>Monday 2 March</td>
+                    </tr>
+                </tbody>
+            </table>"#,
+        )
+        .await;
+
+        let pairs: Vec<(i32, Option<&str>, &str)> = processor
+            .holidays()
+            .iter()
+            .map(|holiday| {
+                (
+                    holiday.year,
+                    holiday.year_text.as_deref(),
+                    holiday.date.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                (2025, Some("2025/26"), "Monday 2 March"),
+                (2026, None, "Monday 2 March"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_holiday_processor_no_year_columns() {
+        let mut processor = HolidayProcessor::new(
+            r#"<table>
+                <thead><tr><th>Holiday</th><th>Date</th><th>Notes</th></tr></thead>
+                <tbody><tr><th><strong>Labour Day</strong></th><td>2 March</td><td>Statewide</td></tr></tbody>
+            </table>"#
+                .to_string(),
+        );
+
+        let err = processor.run().await.expect_err("Expected no year columns");
+        assert!(matches!(err, ScraperError::NoYearColumns), "{:?}", err);
+        assert!(processor.holidays().is_empty());
+    }
+
+    #[test]
+    fn test_parse_year() {
+        for (text, year) in [
+            ("2024", 2024),
+            ("2024*", 2024),
+            (" 2024 # ", 2024),
+            ("2025/26", 2025),
+            ("2025-2026", 2025),
+            ("2025 \u{2013} 26*", 2025),
+            ("1999/00", 1999),
+        ] {
+            assert_eq!(parse_year(text), Ok(year), "{:?}", text);
+        }
+        for text in [
+            "Notes",
+            "",
+            "24",
+            "20245",
+            "2024a",
+            "2025/6",
+            "2025/2024",
+            "3024",
+        ] {
+            assert!(parse_year(text).is_err(), "{:?}", text);
+        }
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(year, month, day)
+    }
+
+    #[tokio::test]
+    async fn test_holiday_processor_parses_dates() {
+        let html = r#"
+            <table>
+                <thead>
+                    <tr><th>Holiday</th><th>2024</th><th>2025</th></tr>
+                </thead>
+                <tbody>
+                    <tr>
+                        <th><strong>New Year's Day</strong></th>
+                        <td>Monday 1 January</td><td>1 January</td>
+                    </tr>
+                    <tr>
+                        <th><strong>Australia Day</strong></th>
+                        <td>Fri 26 Jan</td><td>Monday 27 January*</td>
+                    </tr>
+                    <tr>
+                        <th><strong>Royal Show Day</strong></th>
+                        <td>Monday 30 September</td><td>To be proclaimed</td>
+                    </tr>
+                </tbody>
+            </table>
+        "#
+        .to_string();
+
+        let mut processor = HolidayProcessor::new(html);
+        processor.run().await.expect("Processor failed");
+
+        let parsed: Vec<Option<NaiveDate>> = processor
+            .holidays()
+            .iter()
+            .map(|holiday| holiday.parsed_date)
+            .collect();
+        assert_eq!(
+            parsed,
+            vec![
+                date(2024, 1, 1),
+                date(2025, 1, 1),
+                date(2024, 1, 26),
+                date(2025, 1, 27),
+                date(2024, 9, 30),
+                None,
+            ]
+        );
+        // The raw text is kept alongside
+        assert_eq!(processor.holidays()[3].date, "Monday 27 January*");
+        assert_eq!(
+            processor.holidays()[5].display_date(),
+            "To be proclaimed (not announced)"
+        );
+        assert_eq!(
+            processor.holidays()[0].display_date(),
+            "Monday 1 January (2024-01-01)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_holidays_serde_round_trip() {
+        let mut processor = HolidayProcessor::new(
+            r#"<table><thead><tr><th></th><th>2024*</th></tr></thead><tbody>
+            <tr><th><strong>Labor &amp; Workers' Day</strong></th><td>Monday 4 March</td></tr>
+            <tr><th><strong>Show Day</strong></th><td>TBA</td></tr>
+            </tbody></table>"#
+                .to_string(),
+        );
+        processor.run().await.expect("Processor failed");
+
+        let json = serde_json::to_string(processor.holidays()).unwrap();
+        let restored: Vec<Holiday> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, processor.into_holidays());
+        assert!(json.contains(r#""parsed_date":"2024-03-04""#), "{}", json);
+        assert!(json.contains(r#""parsed_date":null"#), "{}", json);
+    }
+
+    fn response(body: &str) -> FetchResponse {
+        FetchResponse {
+            body: body.to_string(),
+            status: 200,
+            headers: Default::default(),
+            final_url: "https://example.com/holidays".parse().unwrap(),
+            redirects: Vec::new(),
+            encoding: "UTF-8",
+            attempts: 1,
+            elapsed: Duration::ZERO,
+            fetched_at: SystemTime::UNIX_EPOCH + Duration::from_secs(1_735_689_600),
+            transfer: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_to_json_round_trip() {
+        let mut processor = HolidayProcessor::from_response(&response(
+            r#"<table><thead><tr><th></th><th>2024</th><th>2025</th></tr></thead><tbody>
+            <tr><th><strong>Christmas Day</strong></th><td>Wednesday 25 December</td><td>TBA</td></tr>
+            </tbody></table>"#,
+        ));
+        processor.run().await.expect("Processor failed");
 
-        assert_eq!(processor.holidays[0].year, "2023");
-        assert_eq!(processor.holidays[0].name, "Holiday with No Date");
-        assert_eq!(processor.holidays[0].date, "");
+        let json: serde_json::Value =
+            serde_json::from_str(&processor.to_json(JsonStyle::Pretty).unwrap()).unwrap();
+        assert_eq!(json["source_url"], "https://example.com/holidays");
+        assert_eq!(json["fetched_at"], "2025-01-01T00:00:00Z");
+        assert_eq!(
+            json["holidays"][0],
+            serde_json::json!({
+                "year": 2024,
+                "year_text": null,
+                "date": "Wednesday 25 December",
+                "parsed_date": "2024-12-25",
+                "status": "parsed",
+                "name": "Christmas Day",
+                "table_index": 0,
+                "category": null,
+            })
+        );
+        let holidays: Vec<Holiday> = serde_json::from_value(json["holidays"].clone()).unwrap();
+        assert_eq!(holidays, processor.holidays());
+    }
+
+    #[tokio::test]
+    async fn test_to_json_styles() {
+        let mut processor = HolidayProcessor::new(
+            r#"<table><thead><tr><th></th><th>2024</th></tr></thead><tbody>
+            <tr><th><strong>Anzac Day</strong></th><td>25 April</td></tr>
+            </tbody></table>"#
+                .to_string(),
+        );
+        processor.run().await.expect("Processor failed");
+
+        let compact = processor.to_json(JsonStyle::Compact).unwrap();
+        let pretty = processor.to_json(JsonStyle::Pretty).unwrap();
+        assert!(!compact.contains('\n'));
+        assert!(compact.starts_with(r#"{"source_url":null,"fetched_at":null,"holidays":[{"#));
+        assert!(pretty.lines().count() > 1);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&compact).unwrap(),
+            serde_json::from_str::<serde_json::Value>(&pretty).unwrap()
+        );
+    }
+
+    async fn csv_fixture() -> HolidayProcessor {
+        let mut processor = HolidayProcessor::new(
+            r#"<table><thead><tr><th></th><th>2024</th></tr></thead><tbody>
+            <tr><th><strong>Labor &amp; Workers' Day</strong></th><td>Monday 4 March</td></tr>
+            <tr><th><strong>King's Birthday, "observed"</strong></th><td>Monday 23 September</td></tr>
+            <tr><th><strong>Show Day</strong></th><td>TBA</td></tr>
+            </tbody></table>"#
+                .to_string(),
+        );
+        processor.run().await.expect("Processor failed");
+        processor
+    }
+
+    #[tokio::test]
+    async fn test_to_csv_round_trip() {
+        let processor = csv_fixture().await;
+        for delimiter in [b',', b';', b'\t'] {
+            let options = CsvOptions {
+                delimiter,
+                ..CsvOptions::default()
+            };
+            let csv = processor.to_csv(options).unwrap();
+
+            let mut reader = csv::ReaderBuilder::new()
+                .delimiter(delimiter)
+                .from_reader(csv.as_bytes());
+            assert_eq!(
+                reader.headers().unwrap(),
+                vec!["year", "name", "date", "iso_date", "status"]
+            );
+            let rows: Vec<(i32, String, String, Option<NaiveDate>, DateStatus)> =
+                reader.deserialize().collect::<Result<_, _>>().unwrap();
+            let expected: Vec<_> = processor
+                .holidays()
+                .iter()
+                .map(|holiday| {
+                    (
+                        holiday.year,
+                        holiday.name.clone(),
+                        holiday.date.clone(),
+                        holiday.parsed_date,
+                        holiday.status,
+                    )
+                })
+                .collect();
+            assert_eq!(rows, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_to_csv_quoting() {
+        let csv = csv_fixture()
+            .await
+            .to_csv(CsvOptions {
+                header: false,
+                ..CsvOptions::default()
+            })
+            .unwrap();
+
+        assert_eq!(
+            csv,
+            "2024,Labor & Workers' Day,Monday 4 March,2024-03-04,parsed\n\
+             2024,\"King's Birthday, \"\"observed\"\"\",Monday 23 September,2024-09-23,parsed\n\
+             2024,Show Day,TBA,,not_announced\n"
+        );
+    }
+
+    #[test]
+    fn test_to_csv_empty_keeps_header() {
+        let processor = HolidayProcessor::new(String::new());
+        assert_eq!(
+            processor.to_csv(CsvOptions::default()).unwrap(),
+            "year,name,date,iso_date,status\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_to_ics_matches_fixture() {
+        let mut processor = HolidayProcessor::from_response(&response(
+            r#"<table><thead><tr><th></th><th>2025</th></tr></thead><tbody>
+            <tr><th><strong>New Year's Day</strong></th><td>Wednesday 1 January</td></tr>
+            <tr><th><strong>Western Australia Day (formerly Foundation Day), first Monday in June</strong></th><td>Monday 2 June</td></tr>
+            <tr><th><strong>Royal Show Day</strong></th><td>To be proclaimed</td></tr>
+            <tr><th><strong>Boxing Day</strong></th><td>Friday 26 December</td></tr>
+            </tbody></table>"#,
+        ));
+        processor.run().await.expect("Processor failed");
+
+        let ics = processor.to_ics().unwrap();
+        assert_eq!(ics, include_str!("../tests/fixtures/holidays.ics"));
+        assert!(ics
+            .split("\r\n")
+            .all(|line| line.len() <= 75 && !line.contains('\n')));
+        let undated: Vec<&str> = processor
+            .undated()
+            .iter()
+            .map(|holiday| holiday.name.as_str())
+            .collect();
+        assert_eq!(undated, vec!["Royal Show Day"]);
+    }
+
+    #[tokio::test]
+    async fn test_run_twice_keeps_one_copy() {
+        let conn = Connection::open_in_memory().expect("Failed to open database");
+        let mut processor = HolidayProcessor::new(
+            r#"<table><thead><tr><th></th><th>2024</th><th>2025</th></tr></thead><tbody>
+            <tr><th><strong>Anzac Day</strong></th><td>25 April</td><td>25 April</td></tr>
+            </tbody></table>"#
+                .to_string(),
+        );
+        processor.run().await.expect("Processor failed");
+        processor.run().await.expect("Processor failed");
+
+        assert_eq!(processor.holidays().len(), 2);
+        assert!(processor.conflicts().is_empty());
+        processor.save_to_db(&conn).await.expect("Save failed");
+        let rows: i64 = conn
+            .query_row("SELECT COUNT(*) FROM holidays", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(rows, 2);
+    }
+
+    #[tokio::test]
+    async fn test_dedup_flags_conflicting_dates() {
+        let html = r#"
+            <table>
+                <thead>
+                    <tr><th>Holiday</th><th>2024</th></tr>
+                </thead>
+                <tbody>
+                    <tr><th><strong>Labour Day</strong></th><td>Monday 4 March</td></tr>
+                    <tr><th><strong>Easter Monday</strong></th><td>Monday 1 April</td></tr>
+                    <tr><th><strong>Labour Day</strong></th><td>4 March</td></tr>
+                    <tr><th><strong>Easter Monday</strong></th><td>Monday 8 April</td></tr>
+                    <tr><th><strong>Boxing Day</strong></th><td>TBA</td></tr>
+                    <tr><th><strong>Boxing Day</strong></th><td>TBA</td></tr>
+                </tbody>
+            </table>
+        "#
+        .to_string();
+
+        let mut processor = HolidayProcessor::new(html);
+        processor.run().await.expect("Processor failed");
+
+        // Same parsed date and same unparsed text count as duplicates; first occurrence wins
+        let kept: Vec<(&str, &str)> = processor
+            .holidays()
+            .iter()
+            .map(|holiday| (holiday.name.as_str(), holiday.date.as_str()))
+            .collect();
+        assert_eq!(
+            kept,
+            vec![
+                ("Labour Day", "Monday 4 March"),
+                ("Easter Monday", "Monday 1 April"),
+                ("Easter Monday", "Monday 8 April"),
+                ("Boxing Day", "TBA"),
+            ]
+        );
+        assert_eq!(
+            processor.conflicts(),
+            vec![HolidayConflict {
+                year: 2024,
+                name: "Easter Monday".to_string(),
+                dates: vec!["Monday 1 April".to_string(), "Monday 8 April".to_string()],
+            }]
+        );
+        assert_eq!(processor.dedup(), 0);
+    }
+
+    async fn processed(html: &str) -> HolidayProcessor {
+        let mut processor = HolidayProcessor::new(html.to_string());
+        processor.run().await.expect("Processor failed");
+        processor
+    }
+
+    fn names(holidays: &[&Holiday]) -> Vec<String> {
+        holidays
+            .iter()
+            .map(|holiday| format!("{} {}", holiday.year, holiday.name))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_holiday_queries() {
+        let processor = processed(
+            r#"<table><thead><tr><th></th><th>2024</th><th>2025</th></tr></thead><tbody>
+            <tr><th><strong>Good Friday</strong></th><td>Friday 29 March</td><td>Friday 18 April</td></tr>
+            <tr><th><strong>Easter Monday</strong></th><td>Monday 1 April</td><td>Monday 21 April</td></tr>
+            <tr><th><strong>Royal Show Day</strong></th><td>Monday 30 September</td><td>TBA</td></tr>
+            </tbody></table>"#,
+        )
+        .await;
+
+        assert_eq!(
+            names(&processor.holidays_for_year(2025)),
+            vec![
+                "2025 Good Friday",
+                "2025 Easter Monday",
+                "2025 Royal Show Day"
+            ]
+        );
+        assert!(processor.holidays_for_year(2030).is_empty());
+
+        assert_eq!(
+            names(&processor.find_by_name("EASTER")),
+            vec!["2024 Easter Monday", "2025 Easter Monday"]
+        );
+        assert_eq!(processor.find_by_name("show day").len(), 2);
+        assert!(processor.find_by_name("Melbourne Cup").is_empty());
+
+        let april_2025 =
+            processor.holidays_between(date(2025, 4, 1).unwrap(), date(2025, 4, 30).unwrap());
+        assert_eq!(
+            names(&april_2025),
+            vec!["2025 Good Friday", "2025 Easter Monday"]
+        );
+        // Both ends are included
+        let easter_2024 =
+            processor.holidays_between(date(2024, 3, 29).unwrap(), date(2024, 4, 1).unwrap());
+        assert_eq!(easter_2024.len(), 2);
+        assert!(processor
+            .holidays_between(date(2026, 1, 1).unwrap(), date(2026, 12, 31).unwrap())
+            .is_empty());
+        // A reversed window matches nothing
+        assert!(processor
+            .holidays_between(date(2025, 12, 31).unwrap(), date(2024, 1, 1).unwrap())
+            .is_empty());
+    }
+
+    fn pairings(processor: &HolidayProcessor) -> Vec<(String, i32, String)> {
+        processor
+            .holidays()
+            .iter()
+            .map(|holiday| (holiday.name.clone(), holiday.year, holiday.date.clone()))
+            .collect()
+    }
+
+    fn pairing(name: &str, year: i32, date: &str) -> (String, i32, String) {
+        (name.to_string(), year, date.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_holiday_processor_colspan_keeps_alignment() {
+        let processor = processed(
+            r#"<table>
+                <thead>
+                    <tr><th></th><th>2024</th><th>2025</th><th>TBA</th><th>2026</th><th>2027</th></tr>
+                </thead>
+                <tbody>
+                    <tr>
+                        <th><strong>Royal Show Day</strong></th>
+                        <td>30 September</td>
+                        <td colspan="3">To be proclaimed</td>
+                        <td>27 September</td>
+                    </tr>
+                    <tr>
+                        <th><strong>Boxing Day</strong></th>
+                        <td>26 December</td><td colspan="bogus">26 December</td><td>-</td>
+                        <td>28 December</td><td>27 December</td>
+                    </tr>
+                </tbody>
+            </table>"#,
+        )
+        .await;
+
+        assert_eq!(
+            pairings(&processor),
+            vec![
+                pairing("Royal Show Day", 2024, "30 September"),
+                pairing("Royal Show Day", 2025, "To be proclaimed"),
+                pairing("Royal Show Day", 2026, "To be proclaimed"),
+                pairing("Royal Show Day", 2027, "27 September"),
+                pairing("Boxing Day", 2024, "26 December"),
+                pairing("Boxing Day", 2025, "26 December"),
+                pairing("Boxing Day", 2026, "28 December"),
+                pairing("Boxing Day", 2027, "27 December"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_holiday_processor_rowspan_name() {
+        let processor = processed(
+            r#"<table>
+                <thead><tr><th></th><th>2024</th><th>2025</th></tr></thead>
+                <tbody>
+                    <tr><th rowspan="2"><strong>Christmas Day</strong></th><td>25 December</td><td>25 December</td></tr>
+                    <tr><td>27 December</td><td>26 December</td></tr>
+                    <tr><th rowspan="5"><strong>Boxing Day</strong></th><td>26 December</td><td>29 December</td></tr>
+                </tbody>
+            </table>"#,
+        )
+        .await;
+
+        assert_eq!(
+            pairings(&processor),
+            vec![
+                pairing("Christmas Day", 2024, "25 December"),
+                pairing("Christmas Day", 2025, "25 December"),
+                pairing("Christmas Day", 2024, "27 December"),
+                pairing("Christmas Day", 2025, "26 December"),
+                pairing("Boxing Day", 2024, "26 December"),
+                pairing("Boxing Day", 2025, "29 December"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_holiday_processor_custom_selectors() {
+        let wa = processed(
+            r#"<table>
+                <thead><tr><th>Holiday</th><th>2024</th><th>2025</th></tr></thead>
+                <tbody>
+                    <tr><th><strong>New Year's Day</strong></th><td>Monday 1 January</td><td>Wednesday 1 January</td></tr>
+                    <tr><th><strong>Boxing Day</strong></th><td>Thursday 26 December</td><td>Friday 26 December</td></tr>
+                </tbody>
+            </table>"#,
+        )
+        .await;
+
+        // The same table as NSW lays it out, after an unrelated table
+        let nsw_html = r#"
+            <table><tbody><tr><td>Opening hours</td><td>9am</td></tr></tbody></table>
+            <table class="holidays">
+                <tr class="years"><td>Holiday</td><td>2024</td><td>2025</td></tr>
+                <tr class="holiday"><td>New Year's Day</td><td>Monday 1 January</td><td>Wednesday 1 January</td></tr>
+                <tr class="holiday"><td>Boxing Day</td><td>Thursday 26 December</td><td>Friday 26 December</td></tr>
+            </table>"#;
+        let config = SelectorConfig {
+            table: "table.holidays".to_string(),
+            year_header: "tr.years td".to_string(),
+            row: "tr.holiday".to_string(),
+            name_cell: "td:first-child".to_string(),
+            date_cell: "td:not(:first-child)".to_string(),
+        };
+        let mut nsw = HolidayProcessor::with_selectors(nsw_html.to_string(), config);
+        nsw.run().await.expect("Processor failed");
+
+        assert_eq!(wa.holidays().len(), 4);
+        assert_eq!(nsw.holidays(), wa.holidays());
+    }
+
+    #[tokio::test]
+    async fn test_holiday_processor_invalid_selector() {
+        let config = SelectorConfig {
+            row: "tr:nope".to_string(),
+            ..SelectorConfig::default()
+        };
+        let mut processor = HolidayProcessor::with_selectors(String::new(), config);
+
+        let result = processor.run().await;
+        assert!(
+            matches!(&result, Err(ScraperError::SelectorError(message)) if message.starts_with("row selector")),
+            "{:?}",
+            result
+        );
+    }
+
+    const TWO_TABLES: &str = r#"
+        <h2>Public holidays</h2>
+        <table>
+            <thead><tr><th></th><th>2024</th><th>2025</th></tr></thead>
+            <tbody>
+                <tr><th><strong>Labour Day</strong></th><td>Monday 4 March</td><td>Monday 3 March</td></tr>
+            </tbody>
+        </table>
+        <div>
+            <h3>Regional holidays</h3>
+            <table>
+                <caption>Show days</caption>
+                <thead><tr><th></th><th>2025</th><th>2026</th><th>2027</th></tr></thead>
+                <tbody>
+                    <tr><th><strong>Albany Show</strong></th><td>7 November</td><td>6 November</td><td>5 November</td></tr>
+                </tbody>
+            </table>
+            <table>
+                <thead><tr><th></th><th>2026</th></tr></thead>
+                <tbody>
+                    <tr><th><strong>Broome Show</strong></th><td>Friday 26 June</td></tr>
+                </tbody>
+            </table>
+        </div>"#;
+
+    #[tokio::test]
+    async fn test_holiday_processor_multiple_tables() {
+        let processor = processed(TWO_TABLES).await;
+
+        let tagged: Vec<(usize, Option<&str>, String, i32, String)> = processor
+            .holidays()
+            .iter()
+            .map(|holiday| {
+                (
+                    holiday.table_index,
+                    holiday.category.as_deref(),
+                    holiday.name.clone(),
+                    holiday.year,
+                    holiday.date.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            tagged,
+            vec![
+                (
+                    0,
+                    Some("Public holidays"),
+                    "Labour Day".to_string(),
+                    2024,
+                    "Monday 4 March".to_string()
+                ),
+                (
+                    0,
+                    Some("Public holidays"),
+                    "Labour Day".to_string(),
+                    2025,
+                    "Monday 3 March".to_string()
+                ),
+                (
+                    1,
+                    Some("Show days"),
+                    "Albany Show".to_string(),
+                    2025,
+                    "7 November".to_string()
+                ),
+                (
+                    1,
+                    Some("Show days"),
+                    "Albany Show".to_string(),
+                    2026,
+                    "6 November".to_string()
+                ),
+                (
+                    1,
+                    Some("Show days"),
+                    "Albany Show".to_string(),
+                    2027,
+                    "5 November".to_string()
+                ),
+                (
+                    2,
+                    Some("Regional holidays"),
+                    "Broome Show".to_string(),
+                    2026,
+                    "Friday 26 June".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_holiday_processor_first_table_only() {
+        let mut processor = HolidayProcessor::new(TWO_TABLES.to_string()).first_table_only(true);
+        processor.run().await.expect("Processor failed");
+
+        assert_eq!(processor.holidays().len(), 2);
+        assert!(processor
+            .holidays()
+            .iter()
+            .all(|holiday| holiday.name == "Labour Day"));
+    }
+
+    #[tokio::test]
+    async fn test_save_to_db_stores_table() {
+        let conn = Connection::open_in_memory().expect("Failed to open database");
+        let processor = processed(TWO_TABLES).await;
+        processor.save_to_db(&conn).await.expect("Save failed");
+
+        let row: (usize, Option<String>) = conn
+            .query_row(
+                "SELECT table_index, category FROM holidays WHERE name = 'Albany Show' LIMIT 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(row, (1, Some("Show days".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_holiday_processor_classifies_placeholders() {
+        let processor = processed(
+            r#"<table>
+                <thead><tr><th></th><th>2026</th><th>2027</th><th>2028</th><th>2029</th><th>2030</th></tr></thead>
+                <tbody>
+                    <tr>
+                        <th><strong>Royal Show Day</strong></th>
+                        <td>Monday 28 September</td><td>To be proclaimed</td><td>TBA</td>
+                        <td>tbc*</td><td>&mdash;</td>
+                    </tr>
+                    <tr>
+                        <th><strong>King's Birthday</strong></th>
+                        <td>-</td><td>TBC</td><td>&ndash;</td><td>Early spring</td><td></td>
+                    </tr>
+                </tbody>
+            </table>"#,
+        )
+        .await;
+
+        let statuses: Vec<DateStatus> = processor
+            .holidays()
+            .iter()
+            .map(|holiday| holiday.status)
+            .collect();
+        use DateStatus::{NotAnnounced, Parsed, Unparsed};
+        assert_eq!(
+            statuses,
+            vec![
+                Parsed,
+                NotAnnounced,
+                NotAnnounced,
+                NotAnnounced,
+                NotAnnounced,
+                NotAnnounced,
+                NotAnnounced,
+                NotAnnounced,
+                Unparsed,
+                Unparsed,
+            ]
+        );
+        assert_eq!(processor.holidays()[0].parsed_date, date(2026, 9, 28));
+        assert!(processor.holidays()[1..]
+            .iter()
+            .all(|holiday| holiday.parsed_date.is_none()));
+        assert_eq!(
+            processor.holidays()[1].display_date(),
+            "To be proclaimed (not announced)"
+        );
+        // However the placeholder is written, they're the same day
+        assert!(processor.conflicts().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_save_to_db_stores_status() {
+        let conn = Connection::open_in_memory().expect("Failed to open database");
+        let processor = processed(
+            r#"<table><thead><tr><th></th><th>2024</th><th>2025</th><th>2026</th></tr></thead><tbody>
+            <tr><th><strong>Show Day</strong></th><td>Monday 30 September</td><td>To be proclaimed</td><td>Spring</td></tr>
+            </tbody></table>"#,
+        )
+        .await;
+        processor.save_to_db(&conn).await.expect("Save failed");
+
+        let mut stmt = conn
+            .prepare("SELECT parsed_date, status FROM holidays ORDER BY id")
+            .unwrap();
+        let rows: Vec<(Option<String>, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                (Some("2024-09-30".to_string()), "parsed".to_string()),
+                (None, "not_announced".to_string()),
+                (None, "unparsed".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_save_to_db_stores_parsed_date() {
+        let conn = Connection::open_in_memory().expect("Failed to open database");
+        let mut processor = HolidayProcessor::new(
+            r#"<table><thead><tr><th></th><th>2024</th></tr></thead><tbody>
+            <tr><th><strong>Boxing Day</strong></th><td>Thursday 26 December</td></tr>
+            <tr><th><strong>Show Day</strong></th><td>TBA</td></tr>
+            </tbody></table>"#
+                .to_string(),
+        );
+        processor.run().await.expect("Processor failed");
+        processor.save_to_db(&conn).await.expect("Save failed");
+
+        let mut stmt = conn
+            .prepare("SELECT name, parsed_date FROM holidays ORDER BY id")
+            .unwrap();
+        let rows: Vec<(String, Option<String>)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                ("Boxing Day".to_string(), Some("2024-12-26".to_string())),
+                ("Show Day".to_string(), None),
+            ]
+        );
+        processor.fetch_from_db(&conn).await.expect("Fetch failed");
+    }
+
+    #[tokio::test]
+    async fn test_save_to_db_migrates_legacy_year_column() {
+        let conn = Connection::open_in_memory().expect("Failed to open database");
+        conn.execute_batch(
+            "CREATE TABLE holidays (
+                id INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                date TEXT NOT NULL,
+                year TEXT NOT NULL
+            );
+            INSERT INTO holidays (name, date, year) VALUES ('New Year''s Day', 'January 1', '2023');
+            INSERT INTO holidays (name, date, year) VALUES ('Easter Monday', 'April 1', '2024*');
+            INSERT INTO holidays (name, date, year) VALUES ('Show Day', 'TBA', 'TBA');",
+        )
+        .expect("Failed to create legacy table");
+
+        let processor = HolidayProcessor::new(String::new());
+        processor.save_to_db(&conn).await.expect("Save failed");
+
+        let year_type: String = conn
+            .query_row(
+                "SELECT type FROM pragma_table_info('holidays') WHERE name = 'year'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(year_type, "INTEGER");
+
+        let mut stmt = conn
+            .prepare("SELECT name, year, year_text FROM holidays ORDER BY id")
+            .unwrap();
+        let rows: Vec<(String, i32, Option<String>)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                ("New Year's Day".to_string(), 2023, None),
+                ("Easter Monday".to_string(), 2024, Some("2024*".to_string())),
+            ]
+        );
+
+        // The row whose year isn't a number is kept as it was
+        let unmigrated: (i64, String, String, String) = conn
+            .query_row(
+                "SELECT id, name, date, year FROM holidays_legacy_unmigrated",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            unmigrated,
+            (
+                3,
+                "Show Day".to_string(),
+                "TBA".to_string(),
+                "TBA".to_string()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_from_db_migrates_legacy_year_column() {
+        let conn = Connection::open_in_memory().expect("Failed to open database");
+        conn.execute_batch(
+            "CREATE TABLE holidays (
+                id INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                date TEXT NOT NULL,
+                year TEXT NOT NULL
+            );
+            INSERT INTO holidays (name, date, year) VALUES ('Easter Monday', 'April 1', '2024*');",
+        )
+        .expect("Failed to create legacy table");
+
+        let processor = HolidayProcessor::new(String::new());
+        processor.fetch_from_db(&conn).await.expect("Fetch failed");
+
+        let row: (i32, Option<String>) = conn
+            .query_row("SELECT year, year_text FROM holidays", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(row, (2024, Some("2024*".to_string())));
+    }
+
+    /// Rows that each go wrong in a different way, between two rows that don't
+    const MALFORMED_ROWS: &str = r#"<table>
+        <thead><tr><th>Holiday</th><th>2024</th><th>2025</th></tr></thead>
+        <tbody>
+            <tr><th><strong>New Year's Day</strong></th><td>1 January</td><td>1 January</td></tr>
+            <tr><th>Australia Day</th><td>26 January</td><td>27 January</td></tr>
+            <tr><th><strong>Labour Day</strong></th><td>4 March</td></tr>
+            <tr><th><strong>Easter Monday</strong></th><td>1 April</td><td> </td></tr>
+            <tr><th></th><th>2024</th><th>2025</th></tr>
+            <tr><th><strong>Christmas Day</strong></th><td>25 December</td><td>25 December</td></tr>
+        </tbody>
+    </table>"#;
+
+    #[tokio::test]
+    async fn test_holiday_processor_report_lenient() {
+        let processor = processed(MALFORMED_ROWS).await;
+
+        assert_eq!(processor.holidays().len(), 7);
+        let report = processor.report();
+        assert_eq!(
+            (report.rows_seen, report.rows_parsed, report.rows_skipped),
+            (6, 4, 2)
+        );
+        let warnings: Vec<(usize, &WarningReason)> = report
+            .warnings
+            .iter()
+            .map(|warning| (warning.row_index, &warning.reason))
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                (1, &WarningReason::MissingName),
+                (
+                    2,
+                    &WarningReason::CellCountMismatch {
+                        cells: 1,
+                        columns: 2
+                    }
+                ),
+                (3, &WarningReason::EmptyDate { year: 2025 }),
+            ]
+        );
+        assert_eq!(
+            report.warnings[0].snippet,
+            "<tr><th>Australia Day</th><td>26 January</td><td>27 January</td></tr>"
+        );
+        assert!(report
+            .warnings
+            .iter()
+            .all(|warning| warning.table_index == 0));
+    }
+
+    #[tokio::test]
+    async fn test_holiday_processor_report_strict() {
+        let mut processor = HolidayProcessor::new(MALFORMED_ROWS.to_string());
+        let err = processor.run_strict().await.expect_err("Expected warnings");
+        match &err {
+            ScraperError::ParseError(report) => assert_eq!(report, processor.report()),
+            other => panic!("Unexpected error: {:?}", other),
+        }
+        assert!(err.to_string().starts_with(
+            "Parse error: 3 warnings in 6 rows:\n  - table 0 row 1: no holiday name in <tr>"
+        ));
+        // What could be read is still there to look at
+        assert_eq!(processor.holidays().len(), 7);
+
+        let mut processor = HolidayProcessor::new(
+            include_str!("../tests/fixtures/wa_public_holidays.html").to_string(),
+        );
+        processor
+            .run_strict()
+            .await
+            .expect("Expected a clean parse");
+        let report = processor.report();
+        assert!(report.rows_seen > 0);
+        assert_eq!(report.rows_seen, report.rows_parsed);
+    }
+
+    #[tokio::test]
+    async fn test_holiday_processor_duplicate_year_column() {
+        let html = r#"
+            <table>
+                <thead>
+                    <tr><th>Holiday</th><th>2024</th><th>2024</th><th>2025</th></tr>
+                </thead>
+                <tbody>
+                    <tr>
+                        <th><strong>Anzac Day</strong></th>
+                        <td>25 April</td><td>25 April</td><td>25 April</td>
+                    </tr>
+                </tbody>
+            </table>
+        "#
+        .to_string();
+
+        let mut processor = HolidayProcessor::new(html);
+        processor.run().await.expect("Processor failed");
+
+        assert_eq!(processor.holidays().len(), 2);
+        assert_eq!(processor.holidays()[0].year, 2024);
+        assert_eq!(processor.holidays()[1].year, 2025);
+    }
+
+    #[tokio::test]
+    async fn test_holiday_processor_repeated_header_row() {
+        let html = r#"
+            <table>
+                <thead>
+                    <tr><th>Holiday</th><th>2024</th><th>2025</th></tr>
+                </thead>
+                <tbody>
+                    <tr>
+                        <th><strong>Labour Day</strong></th>
+                        <td>4 March</td><td>3 March</td>
+                    </tr>
+                    <tr>
+                        <th><strong>Holiday</strong></th>
+                        <td>2024</td><td>2025</td>
+                    </tr>
+                    <tr>
+                        <th><strong>Boxing Day</strong></th>
+                        <td>26 December</td><td>26 December</td>
+                    </tr>
+                </tbody>
+            </table>
+        "#
+        .to_string();
+
+        let mut processor = HolidayProcessor::new(html);
+        processor.run().await.expect("Processor failed");
+
+        assert_eq!(processor.holidays().len(), 4);
+        assert_eq!(processor.holidays()[1].name, "Labour Day");
+        assert_eq!(processor.holidays()[2].name, "Boxing Day");
+        assert_eq!(processor.holidays()[2].year, 2024);
+    }
+
+    #[tokio::test]
+    async fn test_save_to_db_rolls_back_failed_insert() {
+        let conn = Connection::open_in_memory().expect("Failed to open database");
+        conn.execute_batch(
+            "CREATE TABLE holidays (
+                id INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                date TEXT NOT NULL CHECK (date != 'bad'),
+                year INTEGER NOT NULL,
+                year_text TEXT
+            );",
+        )
+        .unwrap();
+
+        let mut processor = HolidayProcessor::new(
+            r#"<table><thead><tr><th></th><th>2024</th></tr></thead><tbody>
+            <tr><th><strong>Australia Day</strong></th><td>26 January</td></tr>
+            <tr><th><strong>Broken</strong></th><td>bad</td></tr>
+            </tbody></table>"#
+                .to_string(),
+        );
+        processor.run().await.expect("Processor failed");
+
+        assert!(processor.save_to_db(&conn).await.is_err());
+        conn.execute_batch("BEGIN IMMEDIATE; ROLLBACK;")
+            .expect("Database should not be locked");
+        let rows: i64 = conn
+            .query_row("SELECT COUNT(*) FROM holidays", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(rows, 0, "No partial rows should be saved");
+    }
+
+    #[tokio::test]
+    async fn test_save_to_db_records_source() {
+        let conn = Connection::open_in_memory().expect("Failed to open database");
+        // A table from before the source columns existed
+        conn.execute_batch(
+            "CREATE TABLE holidays (
+                id INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                date TEXT NOT NULL,
+                year INTEGER NOT NULL,
+                year_text TEXT
+            );",
+        )
+        .expect("Failed to create table");
+
+        let response = response("<table><thead><tr><th></th><th>2025</th></tr></thead><tbody><tr><th><strong>New Year's Day</strong></th><td>1 January</td></tr></tbody></table>");
+        let mut processor = HolidayProcessor::from_response(&response);
+        processor.run().await.expect("Processor failed");
+        processor.save_to_db(&conn).await.expect("Save failed");
+
+        let row: (String, String, i64) = conn
+            .query_row(
+                "SELECT name, source_url, fetched_at FROM holidays",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            row,
+            (
+                "New Year's Day".to_string(),
+                "https://example.com/holidays".to_string(),
+                1_735_689_600
+            )
+        );
     }
 }
@@ -1,3 +1,14 @@
+pub mod change_tracker;
+pub mod clock;
 pub mod errors;
+pub mod export;
+pub mod fetcher;
+pub mod holiday_date;
 pub mod holiday_processor;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod normalize;
+pub mod parse_report;
+pub mod pipeline;
 pub mod scraper_client;
+pub mod selectors;
@@ -1,29 +1,68 @@
+use log::{error, warn};
+use reqwest::StatusCode;
 use rusqlite::Connection;
+use rust_assignment::change_tracker::ChangeTracker;
 use rust_assignment::errors::ScraperError;
-use rust_assignment::holiday_processor::HolidayProcessor;
+use rust_assignment::pipeline::{scrape_holidays, scrape_holidays_if_changed};
 use rust_assignment::scraper_client::ScraperClient;
 
 #[tokio::main]
-async fn main() -> Result<(), ScraperError> {
+async fn main() {
+    init_logging();
+
+    if let Err(err) = run().await {
+        error!("{}", err);
+        if err.status() == Some(StatusCode::FORBIDDEN) {
+            warn!("The site refused the request; it may be blocking this User-Agent or IP address");
+        }
+        std::process::exit(err.exit_code());
+    }
+}
+
+/// Log through env_logger, or with the `tracing` feature through a tracing subscriber that
+/// also receives the `log` records and reports how long each fetch and parse took
+#[cfg(not(feature = "tracing"))]
+fn init_logging() {
     env_logger::Builder::from_default_env()
         .filter_level(log::LevelFilter::Info)
         .init();
+}
 
-    let mut scraper_client = ScraperClient::new_http();
-    let conn = Connection::open_in_memory()?;
+#[cfg(feature = "tracing")]
+fn init_logging() {
+    use tracing_subscriber::fmt::format::FmtSpan;
+
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .with_span_events(FmtSpan::CLOSE)
+        .init();
+}
 
-    let raw_html = scraper_client
-        .fetch_url(
-            "https://www.commerce.wa.gov.au/labour-relations/public-holidays-western-australia",
-        )
-        .await?;
-    scraper_client.print_stats();
+const URL: &str =
+    "https://www.commerce.wa.gov.au/labour-relations/public-holidays-western-australia";
 
-    let mut processor = HolidayProcessor::new(raw_html);
-    processor.run().await?;
-    processor.pretty_print();
+/// File remembering the page hash between runs; when set, an unchanged page isn't reprocessed
+const HASHES_PATH_VAR: &str = "HOLIDAYS_HASHES_PATH";
 
-    processor.save_to_db(&conn).await?;
+async fn run() -> Result<(), ScraperError> {
+    let scraper_client = ScraperClient::new_http();
+    let conn = Connection::open_in_memory()?;
+
+    let processor = match std::env::var_os(HASHES_PATH_VAR) {
+        Some(path) => {
+            let mut tracker = ChangeTracker::load(path)?;
+            let processor =
+                scrape_holidays_if_changed(&scraper_client, URL, &conn, &mut tracker).await?;
+            tracker.save()?;
+            processor
+        }
+        None => Some(scrape_holidays(&scraper_client, URL, &conn).await?),
+    };
+    scraper_client.log_stats();
+    let Some(processor) = processor else {
+        return Ok(());
+    };
+    processor.pretty_print();
     processor.fetch_from_db(&conn).await?;
 
     Ok(())
@@ -0,0 +1,178 @@
+//! Prometheus metrics in the OpenMetrics text format, behind the `metrics` feature
+
+use crate::holiday_processor::HolidayProcessor;
+use crate::scraper_client::ScraperClient;
+use std::fmt::{Display, Write};
+use std::time::Duration;
+
+/// Upper bounds, in seconds, of the `request_duration_seconds` buckets
+const DURATION_BUCKETS: [f64; 10] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+/// Render the client's request metrics and the number of holidays `processors` parsed
+pub fn render_prometheus(client: &ScraperClient, processors: &[&HolidayProcessor]) -> String {
+    let mut out = MetricsWriter::default();
+    client.write_metrics(&mut out);
+    out.family(
+        "holidays_parsed",
+        "gauge",
+        "Holidays parsed from the fetched pages",
+    );
+    let parsed: usize = processors
+        .iter()
+        .map(|processor| processor.holidays_parsed())
+        .sum();
+    out.sample("holidays_parsed", &[], parsed);
+    out.finish()
+}
+
+/// Cumulative histogram of call durations, as Prometheus expects
+#[derive(Debug, Default)]
+pub(crate) struct DurationHistogram {
+    /// Observations per bucket, not yet cumulative; the last slot is `+Inf`
+    counts: [u64; DURATION_BUCKETS.len() + 1],
+    sum: Duration,
+}
+
+impl DurationHistogram {
+    pub(crate) fn observe(&mut self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        let bucket = DURATION_BUCKETS
+            .iter()
+            .position(|bound| seconds <= *bound)
+            .unwrap_or(DURATION_BUCKETS.len());
+        self.counts[bucket] += 1;
+        self.sum += duration;
+    }
+
+    pub(crate) fn write(&self, out: &mut MetricsWriter, name: &str) {
+        let mut cumulative = 0;
+        for (bound, count) in DURATION_BUCKETS.iter().zip(self.counts) {
+            cumulative += count;
+            out.sample(
+                &format!("{}_bucket", name),
+                &[("le", &bound.to_string())],
+                cumulative,
+            );
+        }
+        cumulative += self.counts[DURATION_BUCKETS.len()];
+        out.sample(&format!("{}_bucket", name), &[("le", "+Inf")], cumulative);
+        out.sample(&format!("{}_sum", name), &[], self.sum.as_secs_f64());
+        out.sample(&format!("{}_count", name), &[], cumulative);
+    }
+}
+
+/// Builds the exposition text one metric family at a time
+#[derive(Debug, Default)]
+pub(crate) struct MetricsWriter {
+    text: String,
+}
+
+impl MetricsWriter {
+    pub(crate) fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.text, "# TYPE {} {}", name, kind);
+        let _ = writeln!(self.text, "# HELP {} {}", name, help);
+    }
+
+    pub(crate) fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl Display) {
+        self.text.push_str(name);
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(label, value)| format!("{}=\"{}\"", label, escape_label(value)))
+                .collect();
+            let _ = write!(self.text, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(self.text, " {}", value);
+    }
+
+    fn finish(mut self) -> String {
+        self.text.push_str("# EOF\n");
+        self.text
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use std::sync::Arc;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        let mut histogram = DurationHistogram::default();
+        histogram.observe(Duration::from_millis(40));
+        histogram.observe(Duration::from_millis(300));
+        histogram.observe(Duration::from_secs(90));
+        let mut out = MetricsWriter::default();
+        histogram.write(&mut out, "d");
+
+        assert!(out.text.contains("d_bucket{le=\"0.05\"} 1\n"));
+        assert!(out.text.contains("d_bucket{le=\"0.25\"} 1\n"));
+        assert!(out.text.contains("d_bucket{le=\"0.5\"} 2\n"));
+        assert!(out.text.contains("d_bucket{le=\"60\"} 2\n"));
+        assert!(out.text.contains("d_bucket{le=\"+Inf\"} 3\n"));
+        assert!(out.text.contains("d_sum 90.34\n"));
+        assert!(out.text.contains("d_count 3\n"));
+    }
+
+    #[test]
+    fn test_label_values_are_escaped() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+
+    const PAGE: &str = "<table><thead><tr><th></th><th>2024</th></tr></thead>\
+        <tbody><tr><th><strong>Boxing Day</strong></th><td>26 December</td></tr></tbody></table>";
+
+    #[tokio::test]
+    async fn test_render_prometheus_after_fetches() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/holidays"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(PAGE, "text/html"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/missing"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+
+        let client = ScraperClient::builder()
+            .max_retries(1)
+            .clock(Arc::new(MockClock::default()))
+            .build()
+            .unwrap();
+        let html = client
+            .fetch_url(format!("{}/holidays", server.uri()).as_str())
+            .await
+            .unwrap();
+        assert!(client
+            .fetch_url(format!("{}/missing", server.uri()).as_str())
+            .await
+            .is_err());
+        let mut processor = HolidayProcessor::new(html);
+        processor.run().await.unwrap();
+
+        let text = render_prometheus(&client, &[&processor]);
+
+        assert!(text.contains("# TYPE requests counter\n"));
+        assert!(text.contains("requests_total{host=\"127.0.0.1\",outcome=\"success\"} 1\n"));
+        assert!(text.contains("requests_total{host=\"127.0.0.1\",outcome=\"failure\"} 1\n"));
+        assert!(text.contains("retries_total 1\n"));
+        assert!(text.contains(&format!("bytes_downloaded_total {}\n", PAGE.len())));
+        assert!(text.contains("# TYPE request_duration_seconds histogram\n"));
+        assert!(text.contains("request_duration_seconds_count 2\n"));
+        assert!(text.contains("# TYPE holidays_parsed gauge\n"));
+        assert!(text.contains("holidays_parsed 1\n"));
+        assert!(text.ends_with("# EOF\n"));
+    }
+}
@@ -0,0 +1,126 @@
+use scraper::{ElementRef, Node};
+use std::borrow::Cow;
+
+/// The text of a table cell with any nested markup dropped, `<br>` read as a space and whitespace
+/// collapsed and trimmed
+///
+/// Entities such as `&amp;` or `&#8217;` are already decoded by the HTML parser.
+pub fn cell_text(cell: &ElementRef) -> String {
+    let mut text = String::new();
+    for node in cell.descendants() {
+        match node.value() {
+            Node::Text(fragment) => text.push_str(fragment),
+            Node::Element(element) if element.name() == "br" => text.push(' '),
+            _ => {}
+        }
+    }
+    collapse_whitespace(&text).trim().to_string()
+}
+
+/// Collapse every run of whitespace (including Unicode whitespace) into a single space
+///
+/// Behaves like `Regex::new(r"\s+").replace_all(text, " ")` and borrows the input when
+/// nothing needs to change.
+pub fn collapse_whitespace(text: &str) -> Cow<'_, str> {
+    if !needs_collapsing(text) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut collapsed = String::with_capacity(text.len());
+    let mut in_whitespace = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !in_whitespace {
+                collapsed.push(' ');
+            }
+            in_whitespace = true;
+        } else {
+            collapsed.push(c);
+            in_whitespace = false;
+        }
+    }
+    Cow::Owned(collapsed)
+}
+
+/// Whether the text contains a whitespace run longer than one or any whitespace other than ' '
+fn needs_collapsing(text: &str) -> bool {
+    let mut previous_whitespace = false;
+    for c in text.chars() {
+        let whitespace = c.is_whitespace();
+        if whitespace && (previous_whitespace || c != ' ') {
+            return true;
+        }
+        previous_whitespace = whitespace;
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use regex::Regex;
+    use scraper::{Html, Selector};
+
+    fn collapse_with_regex(text: &str) -> String {
+        Regex::new(r"\s+")
+            .unwrap()
+            .replace_all(text, " ")
+            .into_owned()
+    }
+
+    fn first_cell(html: &str) -> String {
+        let fragment = Html::parse_fragment(&format!("<table><tr>{}</tr></table>", html));
+        let selector = Selector::parse("td").unwrap();
+        let cell = fragment.select(&selector).next().unwrap();
+        cell_text(&cell)
+    }
+
+    #[test]
+    fn test_cell_text_drops_markup() {
+        assert_eq!(
+            first_cell(r#"<td><a href="/x"><span>27</span> September</a><sup>*</sup></td>"#),
+            "27 September*"
+        );
+        assert_eq!(
+            first_cell("<td>King&#8217;s<br>Birthday&nbsp;</td>"),
+            "King\u{2019}s Birthday"
+        );
+        assert_eq!(
+            first_cell("<td> 1 &ndash; 2 <!-- note --> May </td>"),
+            "1 \u{2013} 2 May"
+        );
+        // Decoded once: an escaped entity stays as written
+        assert_eq!(first_cell("<td>&amp;lt;</td>"), "&lt;");
+    }
+
+    #[test]
+    fn test_collapse_whitespace_borrows_clean_input() {
+        assert!(matches!(
+            collapse_whitespace("Monday 1 January"),
+            Cow::Borrowed("Monday 1 January")
+        ));
+    }
+
+    #[test]
+    fn test_collapse_whitespace_mixed_whitespace() {
+        assert_eq!(
+            collapse_whitespace("May\u{a0}1 \t\n\u{2003} 2023 "),
+            "May 1 2023 "
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_collapse_whitespace_matches_regex(
+            text in "[a-z \t\r\n\u{a0}\u{2003}\u{3000}\u{200b}]{0,40}"
+        ) {
+            prop_assert_eq!(collapse_whitespace(&text).into_owned(), collapse_with_regex(&text));
+        }
+
+        #[test]
+        fn test_collapse_whitespace_matches_regex_any_text(text in any::<String>()) {
+            prop_assert_eq!(collapse_whitespace(&text).into_owned(), collapse_with_regex(&text));
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use crate::normalize::collapse_whitespace;
+use log::warn;
+use scraper::ElementRef;
+use std::fmt;
+
+/// How much of a row's HTML a [`ParseWarning`] keeps
+const SNIPPET_CHARS: usize = 120;
+
+/// What [`crate::holiday_processor::HolidayProcessor::run`] made of the table rows
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseReport {
+    /// Body rows in the tables that have year columns
+    pub rows_seen: usize,
+    /// Rows that gave at least one holiday
+    pub rows_parsed: usize,
+    /// Rows that gave none, such as repeated header rows and rows without a name
+    pub rows_skipped: usize,
+    pub warnings: Vec<ParseWarning>,
+}
+
+/// A row that was skipped or only partly read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseWarning {
+    /// Position of the table among the tables on the page, from 0
+    pub table_index: usize,
+    /// Position of the row among the table's body rows, from 0
+    pub row_index: usize,
+    /// The start of the row's HTML with whitespace collapsed
+    pub snippet: String,
+    pub reason: WarningReason,
+}
+
+/// Why a row gave a [`ParseWarning`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WarningReason {
+    /// No name cell, and no name cell above spanning down to it; the row is skipped
+    MissingName,
+    /// The date cells cover a different number of columns than the header has, so some years
+    /// got no date or some dates no year
+    CellCountMismatch { cells: usize, columns: usize },
+    /// The date cell for `year` is empty
+    EmptyDate { year: i32 },
+}
+
+impl ParseReport {
+    /// Record and log a warning about `row`
+    pub(crate) fn warn(
+        &mut self,
+        table_index: usize,
+        row_index: usize,
+        row: &ElementRef,
+        reason: WarningReason,
+    ) {
+        warn!("Row {} of table {}: {}", row_index, table_index, reason);
+        let html = row.html();
+        let snippet = collapse_whitespace(html.trim())
+            .chars()
+            .take(SNIPPET_CHARS)
+            .collect();
+        self.warnings.push(ParseWarning {
+            table_index,
+            row_index,
+            snippet,
+            reason,
+        });
+    }
+}
+
+impl fmt::Display for ParseReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} warnings in {} rows:",
+            self.warnings.len(),
+            self.rows_seen
+        )?;
+        for warning in &self.warnings {
+            write!(
+                f,
+                "\n  - table {} row {}: {} in {}",
+                warning.table_index, warning.row_index, warning.reason, warning.snippet
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for WarningReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WarningReason::MissingName => f.write_str("no holiday name"),
+            WarningReason::CellCountMismatch { cells, columns } => write!(
+                f,
+                "date cells cover {} columns but the header has {}",
+                cells, columns
+            ),
+            WarningReason::EmptyDate { year } => write!(f, "empty {} date", year),
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use crate::change_tracker::ChangeTracker;
+use crate::errors::ScraperError;
+use crate::fetcher::Fetcher;
+use crate::holiday_processor::HolidayProcessor;
+use crate::scraper_client::FetchResponse;
+use log::{info, warn};
+use rusqlite::Connection;
+
+/// Fetch the holidays page at `url`, parse it and save the holidays into `conn`
+pub async fn scrape_holidays<F: Fetcher + ?Sized>(
+    fetcher: &F,
+    url: &str,
+    conn: &Connection,
+) -> Result<HolidayProcessor, ScraperError> {
+    let response = fetcher.fetch(url).await?;
+    process(&response, conn).await
+}
+
+/// Like [`scrape_holidays`], but skipping parsing and saving when `tracker` has seen the same
+/// page before
+///
+/// Returns `None` for an unchanged page. The page is only recorded in `tracker` once it has been
+/// parsed and saved, so after a failure the next call tries it again. The tracker isn't saved
+/// to disk here; callers do that once the whole run has succeeded.
+pub async fn scrape_holidays_if_changed<F: Fetcher + ?Sized>(
+    fetcher: &F,
+    url: &str,
+    conn: &Connection,
+    tracker: &mut ChangeTracker,
+) -> Result<Option<HolidayProcessor>, ScraperError> {
+    let response = fetcher.fetch(url).await?;
+    if !tracker.is_changed(url, &response.body) {
+        info!("{} hasn't changed since the last run", url);
+        return Ok(None);
+    }
+    let processor = process(&response, conn).await?;
+    tracker.record(url, &response.body);
+    Ok(Some(processor))
+}
+
+async fn process(
+    response: &FetchResponse,
+    conn: &Connection,
+) -> Result<HolidayProcessor, ScraperError> {
+    if response.moved_permanently() {
+        warn!(
+            "Content now lives at {}, update the URL",
+            response.final_url
+        );
+    }
+
+    let mut processor = HolidayProcessor::from_response(response);
+    processor.run().await?;
+    processor.save_to_db(conn).await?;
+    Ok(processor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetcher::StaticFetcher;
+    use reqwest::StatusCode;
+
+    const WA_FIXTURE: &str = include_str!("../tests/fixtures/wa_public_holidays.html");
+    const WA_URL: &str = "https://example.com/public-holidays";
+
+    #[tokio::test]
+    async fn test_scrape_holidays_offline() {
+        let fetcher = StaticFetcher::new().with_page(WA_URL, WA_FIXTURE);
+        let conn = Connection::open_in_memory().unwrap();
+
+        scrape_holidays(&fetcher, WA_URL, &conn).await.unwrap();
+
+        let (count, source_url): (i64, String) = conn
+            .query_row(
+                "SELECT COUNT(*), MIN(source_url) FROM holidays",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(count, 20);
+        assert_eq!(source_url, WA_URL);
+    }
+
+    #[tokio::test]
+    async fn test_scrape_holidays_if_changed_skips_unchanged_page() {
+        let fetcher = StaticFetcher::new().with_page(WA_URL, WA_FIXTURE);
+        let mut tracker = ChangeTracker::new();
+
+        let first = Connection::open_in_memory().unwrap();
+        let processed = scrape_holidays_if_changed(&fetcher, WA_URL, &first, &mut tracker)
+            .await
+            .unwrap();
+        assert!(processed.is_some());
+
+        let second = Connection::open_in_memory().unwrap();
+        let processed = scrape_holidays_if_changed(&fetcher, WA_URL, &second, &mut tracker)
+            .await
+            .unwrap();
+        assert!(processed.is_none());
+        let tables: i64 = second
+            .query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(tables, 0);
+    }
+
+    #[tokio::test]
+    async fn test_scrape_holidays_if_changed_retries_failed_page() {
+        let page = "<table><thead><tr><th></th><th>Notes</th></tr></thead></table>";
+        let fetcher = StaticFetcher::new().with_page(WA_URL, page);
+        let conn = Connection::open_in_memory().unwrap();
+        let mut tracker = ChangeTracker::new();
+
+        // Both calls parse the page rather than the second skipping it as seen
+        for _ in 0..2 {
+            let result = scrape_holidays_if_changed(&fetcher, WA_URL, &conn, &mut tracker).await;
+            assert!(matches!(result, Err(ScraperError::NoYearColumns)));
+        }
+        assert!(tracker.is_changed(WA_URL, page));
+    }
+
+    #[tokio::test]
+    async fn test_scrape_holidays_unknown_page() {
+        let fetcher = StaticFetcher::new();
+        let conn = Connection::open_in_memory().unwrap();
+
+        let result = scrape_holidays(&fetcher, WA_URL, &conn).await;
+
+        assert!(matches!(
+            result,
+            Err(ScraperError::HttpStatus {
+                status: StatusCode::NOT_FOUND,
+                ..
+            })
+        ));
+    }
+}
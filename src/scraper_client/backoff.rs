@@ -0,0 +1,166 @@
+use rand::Rng;
+use reqwest::header::HeaderValue;
+use std::time::{Duration, SystemTime};
+
+/// How long to wait between retry attempts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BackoffPolicy {
+    /// Wait the same delay before every retry
+    Fixed(Duration),
+    /// Double the delay after each retry, starting at `base` and never exceeding `max`
+    ///
+    /// With `jitter` the delay is picked uniformly between half and all of the computed value,
+    /// so concurrent scrapers don't retry in lockstep.
+    Exponential {
+        base: Duration,
+        max: Duration,
+        jitter: bool,
+    },
+}
+
+impl BackoffPolicy {
+    /// Delay before retry number `retry` (starting at 1), before any jitter
+    pub fn base_delay(&self, retry: u32) -> Duration {
+        match *self {
+            BackoffPolicy::Fixed(delay) => delay,
+            BackoffPolicy::Exponential { base, max, .. } => {
+                let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+                base.checked_mul(factor).unwrap_or(max).min(max)
+            }
+        }
+    }
+
+    /// Delay before retry number `retry` (starting at 1), with jitter drawn from `rng`
+    pub fn delay<R: Rng + ?Sized>(&self, retry: u32, rng: &mut R) -> Duration {
+        let delay = self.base_delay(retry);
+        match self {
+            BackoffPolicy::Exponential { jitter: true, .. } => {
+                let half = delay / 2;
+                half + half.mul_f64(rng.gen_range(0.0..=1.0))
+            }
+            _ => delay,
+        }
+    }
+}
+
+impl Default for BackoffPolicy {
+    fn default() -> Self {
+        BackoffPolicy::Fixed(Duration::from_secs(2))
+    }
+}
+
+/// Parse a `Retry-After` header given either as delta-seconds or as an HTTP date
+///
+/// Dates in the past mean "retry now" and give a zero delay; unparseable values give `None`.
+pub fn parse_retry_after(value: &HeaderValue, now: SystemTime) -> Option<Duration> {
+    let value = value.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let retry_at = httpdate::parse_http_date(value).ok()?;
+    Some(retry_at.duration_since(now).unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_fixed_backoff_sequence() {
+        let policy = BackoffPolicy::Fixed(Duration::from_secs(2));
+        let mut rng = StdRng::seed_from_u64(1);
+        let delays: Vec<Duration> = (1..=4).map(|retry| policy.delay(retry, &mut rng)).collect();
+        assert_eq!(delays, vec![Duration::from_secs(2); 4]);
+    }
+
+    #[test]
+    fn test_exponential_backoff_sequence_is_capped() {
+        let policy = BackoffPolicy::Exponential {
+            base: Duration::from_millis(500),
+            max: Duration::from_secs(5),
+            jitter: false,
+        };
+        let mut rng = StdRng::seed_from_u64(1);
+        let delays: Vec<Duration> = (1..=6).map(|retry| policy.delay(retry, &mut rng)).collect();
+        assert_eq!(
+            delays,
+            vec![
+                Duration::from_millis(500),
+                Duration::from_secs(1),
+                Duration::from_secs(2),
+                Duration::from_secs(4),
+                Duration::from_secs(5),
+                Duration::from_secs(5),
+            ]
+        );
+        assert_eq!(policy.base_delay(200), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_exponential_backoff_jitter_bounds() {
+        let policy = BackoffPolicy::Exponential {
+            base: Duration::from_secs(1),
+            max: Duration::from_secs(60),
+            jitter: true,
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+        for retry in 1..=5 {
+            let base = policy.base_delay(retry);
+            for _ in 0..100 {
+                let delay = policy.delay(retry, &mut rng);
+                assert!(
+                    delay >= base / 2 && delay <= base,
+                    "{:?} out of bounds",
+                    delay
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_retry_after_seconds() {
+        let now = SystemTime::UNIX_EPOCH;
+        assert_eq!(
+            parse_retry_after(&HeaderValue::from_static("120"), now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after(&HeaderValue::from_static(" 0 "), now),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_parse_retry_after_http_date() {
+        let now = httpdate::parse_http_date("Wed, 21 Oct 2015 07:28:00 GMT").unwrap();
+        assert_eq!(
+            parse_retry_after(
+                &HeaderValue::from_static("Wed, 21 Oct 2015 07:29:30 GMT"),
+                now
+            ),
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(
+            parse_retry_after(
+                &HeaderValue::from_static("Wed, 21 Oct 2015 07:27:00 GMT"),
+                now
+            ),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_parse_retry_after_invalid() {
+        let now = SystemTime::UNIX_EPOCH;
+        assert_eq!(
+            parse_retry_after(&HeaderValue::from_static("soon"), now),
+            None
+        );
+        assert_eq!(
+            parse_retry_after(&HeaderValue::from_static("-5"), now),
+            None
+        );
+    }
+}
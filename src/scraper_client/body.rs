@@ -0,0 +1,222 @@
+use super::response::Transfer;
+use crate::errors::ScraperError;
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+use flate2::read::{DeflateDecoder, MultiGzDecoder, ZlibDecoder};
+use futures::StreamExt;
+use reqwest::header::{HeaderValue, CONTENT_ENCODING, CONTENT_TYPE};
+use reqwest::Response;
+use std::io::{self, Read};
+
+/// How many leading bytes are searched for a `<meta charset>` declaration
+const META_SNIFF_BYTES: usize = 4096;
+
+/// Whether the client asks servers to compress response bodies
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Compression {
+    /// Accept gzip or deflate and decompress transparently
+    #[default]
+    Auto,
+    /// Send `Accept-Encoding: identity` so bodies arrive uncompressed
+    Disabled,
+    /// Like `Auto`, but warn whenever a server sends a body uncompressed anyway
+    Required,
+}
+
+impl Compression {
+    pub(crate) fn accept_encoding(self) -> HeaderValue {
+        match self {
+            Compression::Auto | Compression::Required => HeaderValue::from_static("gzip, deflate"),
+            Compression::Disabled => HeaderValue::from_static("identity"),
+        }
+    }
+}
+
+/// Read a response body and decode it to UTF-8, returning the encoding that was used and what
+/// was transferred
+///
+/// Fails with `ResponseTooLarge` as soon as the body passes `limit` bytes, on the wire or once
+/// decompressed. A `Content-Length` over the limit fails before anything is read; otherwise the
+/// body is streamed, so an oversized or endless response is never buffered in full.
+pub(crate) async fn read_body(
+    response: Response,
+    limit: u64,
+) -> Result<(String, &'static Encoding, Transfer), ScraperError> {
+    let charset = header_charset(&response);
+    let (body, transfer) = read_bytes(response, limit).await?;
+    let (text, encoding) = decode(&body, charset.as_deref());
+    Ok((text, encoding, transfer))
+}
+
+/// Read a response body as raw decompressed bytes, with the same `limit` handling as
+/// [`read_body`]
+pub(crate) async fn read_bytes(
+    response: Response,
+    limit: u64,
+) -> Result<(Vec<u8>, Transfer), ScraperError> {
+    let url = response.url().to_string();
+    let too_large = || ScraperError::ResponseTooLarge {
+        limit,
+        url: url.clone(),
+    };
+    if response
+        .content_length()
+        .is_some_and(|length| length > limit)
+    {
+        return Err(too_large());
+    }
+
+    let content_encoding = content_encoding(&response);
+    let mut body = Vec::new();
+    let mut chunks = response.bytes_stream();
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk?;
+        if (body.len() + chunk.len()) as u64 > limit {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    let wire_bytes = body.len() as u64;
+    let body = match content_encoding.as_deref() {
+        None => Ok(body),
+        Some("gzip" | "x-gzip") => decompress(MultiGzDecoder::new(&body[..]), limit),
+        // Servers disagree on whether deflate means zlib-wrapped or raw
+        Some("deflate") => decompress(ZlibDecoder::new(&body[..]), limit)
+            .or_else(|_| decompress(DeflateDecoder::new(&body[..]), limit)),
+        Some(other) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unsupported Content-Encoding {:?}", other),
+        )),
+    }
+    .map_err(|e| match e.kind() {
+        io::ErrorKind::FileTooLarge => too_large(),
+        _ => ScraperError::Io(io::Error::new(e.kind(), format!("{}: {}", url, e))),
+    })?;
+    let transfer = Transfer {
+        wire_bytes,
+        decoded_bytes: body.len() as u64,
+        content_encoding,
+    };
+    Ok((body, transfer))
+}
+
+/// Decompress all of `decoder`, failing with `FileTooLarge` past `limit` bytes
+fn decompress(decoder: impl Read, limit: u64) -> io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    decoder.take(limit + 1).read_to_end(&mut body)?;
+    if body.len() as u64 > limit {
+        return Err(io::ErrorKind::FileTooLarge.into());
+    }
+    Ok(body)
+}
+
+/// The `Content-Encoding` header in lower case, `None` when absent or `identity`
+fn content_encoding(response: &Response) -> Option<String> {
+    let encoding = response
+        .headers()
+        .get(CONTENT_ENCODING)?
+        .to_str()
+        .ok()?
+        .trim()
+        .to_ascii_lowercase();
+    (!encoding.is_empty() && encoding != "identity").then_some(encoding)
+}
+
+/// Decode a body using, in order: a byte order mark, the `Content-Type` charset, a
+/// `<meta charset>` near the start of the page, then UTF-8
+///
+/// Older pages often claim or default to UTF-8 while actually being windows-1252 (a superset
+/// of ISO-8859-1), so invalid UTF-8 is decoded as windows-1252 instead of being mangled.
+pub(crate) fn decode(body: &[u8], header_charset: Option<&str>) -> (String, &'static Encoding) {
+    if let Some((encoding, bom_length)) = Encoding::for_bom(body) {
+        let (text, _) = encoding.decode_without_bom_handling(&body[bom_length..]);
+        return (text.into_owned(), encoding);
+    }
+
+    let declared = header_charset
+        .and_then(|charset| Encoding::for_label(charset.as_bytes()))
+        .or_else(|| meta_charset(&body[..body.len().min(META_SNIFF_BYTES)]))
+        .unwrap_or(UTF_8);
+    let (text, had_errors) = declared.decode_without_bom_handling(body);
+    if had_errors && declared == UTF_8 {
+        let (text, _) = WINDOWS_1252.decode_without_bom_handling(body);
+        return (text.into_owned(), WINDOWS_1252);
+    }
+    (text.into_owned(), declared)
+}
+
+/// The charset parameter of the `Content-Type` header, if any
+fn header_charset(response: &Response) -> Option<String> {
+    let content_type = response.headers().get(CONTENT_TYPE)?.to_str().ok()?;
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+/// The encoding from `<meta charset="...">` or `<meta http-equiv="Content-Type" content="...; charset=...">`
+fn meta_charset(head: &[u8]) -> Option<&'static Encoding> {
+    // Declarations are ASCII, so a lossy view is enough to find them in any ASCII-compatible encoding
+    let head = String::from_utf8_lossy(head).to_ascii_lowercase();
+    head.match_indices("<meta").find_map(|(start, _)| {
+        let tag = &head[start..];
+        let tag = &tag[..tag.find('>').unwrap_or(tag.len())];
+        let value = &tag[tag.find("charset=")? + "charset=".len()..];
+        let value = value.trim_start_matches(['"', '\'', ' ']);
+        let end = value
+            .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '.')))
+            .unwrap_or(value.len());
+        Encoding::for_label(&value.as_bytes()[..end])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::ISO_8859_2;
+
+    /// "Café Señor" in ISO-8859-1
+    const LATIN1: &[u8] = b"Caf\xe9 Se\xf1or";
+
+    #[test]
+    fn test_decode_invalid_utf8_as_windows_1252() {
+        let (text, encoding) = decode(LATIN1, None);
+        assert_eq!(text, "Café Señor");
+        assert_eq!(encoding, WINDOWS_1252);
+    }
+
+    #[test]
+    fn test_decode_prefers_header_charset() {
+        // 0xf1 is "ñ" in latin-1 but "ń" in latin-2
+        let (text, encoding) = decode(LATIN1, Some("iso-8859-2"));
+        assert_eq!(text, "Café Seńor");
+        assert_eq!(encoding, ISO_8859_2);
+    }
+
+    #[test]
+    fn test_decode_meta_charset() {
+        let mut page = b"<html><head><meta charset=\"iso-8859-1\"></head><body>".to_vec();
+        page.extend_from_slice(LATIN1);
+        let (text, _) = decode(&page, None);
+        assert!(text.ends_with("Café Señor"), "{}", text);
+
+        let mut page =
+            b"<meta http-equiv='Content-Type' content='text/html; charset=windows-1252'>".to_vec();
+        page.extend_from_slice(b"\x93quoted\x94");
+        let (text, encoding) = decode(&page, None);
+        assert!(text.ends_with("\u{201c}quoted\u{201d}"), "{}", text);
+        assert_eq!(encoding, WINDOWS_1252);
+    }
+
+    #[test]
+    fn test_decode_utf8_and_bom() {
+        assert_eq!(
+            decode("Māori".as_bytes(), None),
+            ("Māori".to_string(), UTF_8)
+        );
+        let (text, encoding) = decode(b"\xef\xbb\xbfM\xc4\x81ori", Some("iso-8859-1"));
+        assert_eq!((text.as_str(), encoding), ("Māori", UTF_8));
+    }
+}
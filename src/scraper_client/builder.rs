@@ -0,0 +1,589 @@
+use super::cache::ResponseCache;
+use super::circuit_breaker::CircuitBreaker;
+use super::content_type;
+use super::disk_cache::DiskCache;
+use super::hooks::Hooks;
+use super::proxy::ProxySettings;
+use super::rate_limit::RateLimiter;
+use super::redirect::RedirectPolicy;
+use super::retry::{is_retryable_status, RetryPredicate};
+use super::snapshot::SnapshotWriter;
+use super::stats::ScraperClientStats;
+use super::tls::TlsSettings;
+use super::user_agent::{UserAgentRotation, UserAgentRotator};
+use super::ScraperClient;
+use super::{BackoffPolicy, Compression, TlsVersion};
+use crate::clock::{Clock, SystemClock};
+use crate::errors::ScraperError;
+use reqwest::cookie::Jar;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT_ENCODING, USER_AGENT};
+use reqwest::{Client, StatusCode};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Configures and builds a [`ScraperClient`]
+///
+/// Header names and values are validated in [`ScraperClientBuilder::build`], so the setters never fail.
+pub struct ScraperClientBuilder {
+    connect_timeout: Duration,
+    request_timeout: Duration,
+    fetch_deadline: Option<Duration>,
+    hedge_delay: Duration,
+    max_retries: u8,
+    backoff: BackoffPolicy,
+    max_retry_after: Duration,
+    retry_on_status: RetryPredicate,
+    min_request_interval: Duration,
+    requests_per_second: Option<f64>,
+    user_agent: String,
+    user_agents: Vec<String>,
+    user_agent_rotation: UserAgentRotation,
+    respect_robots_txt: bool,
+    cookies: bool,
+    proxy: ProxySettings,
+    tls: TlsSettings,
+    compression: Compression,
+    disk_cache: Option<(PathBuf, Duration)>,
+    snapshot_dir: Option<PathBuf>,
+    snapshot_retention: Option<usize>,
+    accepted_content_types: Vec<String>,
+    default_headers: Vec<(String, String)>,
+    retry_posts: bool,
+    clock: Arc<dyn Clock>,
+    clock_skew_threshold: Duration,
+    max_body_bytes: u64,
+    redirect_policy: RedirectPolicy,
+    circuit_breaker: CircuitBreaker,
+}
+
+impl Default for ScraperClientBuilder {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            request_timeout: Duration::from_secs(30),
+            fetch_deadline: None,
+            hedge_delay: Duration::ZERO,
+            max_retries: 3,
+            backoff: BackoffPolicy::default(),
+            max_retry_after: Duration::from_secs(120),
+            retry_on_status: Arc::new(is_retryable_status),
+            min_request_interval: Duration::ZERO,
+            requests_per_second: None,
+            user_agent: "Rust ScraperClient/1.0".to_string(),
+            user_agents: Vec::new(),
+            user_agent_rotation: UserAgentRotation::default(),
+            respect_robots_txt: false,
+            cookies: false,
+            proxy: ProxySettings::default(),
+            tls: TlsSettings::default(),
+            compression: Compression::default(),
+            disk_cache: None,
+            snapshot_dir: None,
+            snapshot_retention: None,
+            accepted_content_types: content_type::DEFAULT_ACCEPTED
+                .iter()
+                .map(|content_type| content_type.to_string())
+                .collect(),
+            default_headers: Vec::new(),
+            retry_posts: false,
+            clock: Arc::new(SystemClock),
+            clock_skew_threshold: Duration::from_secs(60),
+            max_body_bytes: 10 * 1024 * 1024,
+            redirect_policy: RedirectPolicy::default(),
+            circuit_breaker: CircuitBreaker::default(),
+        }
+    }
+}
+
+impl ScraperClientBuilder {
+    /// How long each attempt may take to establish a connection (default 10 seconds)
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// How long each attempt may take overall, from connecting to reading the body (default 30 seconds)
+    ///
+    /// Can be overridden per call with [`ScraperClient::fetch_url_with_timeout`].
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Give up on a fetch once this long has passed since its first attempt, even if retries remain
+    ///
+    /// The last attempt's timeout is shortened to fit, and the fetch fails with
+    /// [`ScraperError::DeadlineExceeded`]. Can be overridden per call with
+    /// [`ScraperClient::fetch_url_with_deadline`]. No deadline by default.
+    pub fn fetch_deadline(mut self, deadline: Duration) -> Self {
+        self.fetch_deadline = Some(deadline);
+        self
+    }
+
+    /// Number of retries after the first failed attempt
+    pub fn max_retries(mut self, max_retries: u8) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Wait the same delay between every attempt
+    pub fn retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.backoff = BackoffPolicy::Fixed(retry_delay);
+        self
+    }
+
+    /// How the delay between attempts grows
+    pub fn backoff(mut self, backoff: BackoffPolicy) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Longest delay a server's `Retry-After` header may impose before the next attempt
+    pub fn max_retry_after(mut self, max_retry_after: Duration) -> Self {
+        self.max_retry_after = max_retry_after;
+        self
+    }
+
+    /// Decide which response statuses are retried, replacing [`is_retryable_status`]
+    pub fn retry_on_status<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(StatusCode) -> bool + Send + Sync + 'static,
+    {
+        self.retry_on_status = Arc::new(predicate);
+        self
+    }
+
+    /// Minimum time between the start of consecutive requests to the same host
+    pub fn min_request_interval(mut self, interval: Duration) -> Self {
+        self.min_request_interval = interval;
+        self
+    }
+
+    /// Limit requests to each host; combined with `min_request_interval` the stricter limit wins
+    pub fn requests_per_second(mut self, requests_per_second: f64) -> Self {
+        self.requests_per_second = Some(requests_per_second);
+        self
+    }
+
+    /// User-Agent sent with every request
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    /// Rotate through these User-Agents, one per request, instead of always sending `user_agent`
+    ///
+    /// robots.txt rules are still matched against the `user_agent` value.
+    pub fn user_agents(mut self, user_agents: &[&str], rotation: UserAgentRotation) -> Self {
+        self.user_agents = user_agents
+            .iter()
+            .map(|user_agent| user_agent.to_string())
+            .collect();
+        self.user_agent_rotation = rotation;
+        self
+    }
+
+    /// Check each URL against the host's robots.txt and honour its Crawl-delay
+    pub fn respect_robots_txt(mut self, enabled: bool) -> Self {
+        self.respect_robots_txt = enabled;
+        self
+    }
+
+    /// Keep cookies set by responses and send them on later requests from the same client
+    pub fn with_cookies(mut self, enabled: bool) -> Self {
+        self.cookies = enabled;
+        self
+    }
+
+    /// Send plain HTTP requests through this proxy; `user:pass@` in the URL enables proxy auth
+    pub fn http_proxy(mut self, url: &str) -> Self {
+        self.proxy.http = Some(url.to_string());
+        self
+    }
+
+    /// Send HTTPS requests through this proxy; `user:pass@` in the URL enables proxy auth
+    pub fn https_proxy(mut self, url: &str) -> Self {
+        self.proxy.https = Some(url.to_string());
+        self
+    }
+
+    /// Whether to ask for compressed bodies; by default gzip and deflate are accepted
+    ///
+    /// An `Accept-Encoding` set with [`ScraperClientBuilder::default_header`] takes precedence.
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Trust this PEM-encoded CA certificate in addition to the system roots, e.g. for an internal CA
+    ///
+    /// The certificate is parsed by [`ScraperClientBuilder::build`], which fails with
+    /// [`ScraperError::Tls`] if it's invalid.
+    pub fn add_root_certificate(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.tls.root_certificates.push(pem.into());
+        self
+    }
+
+    /// Accept any server certificate, including expired, self-signed or mismatched ones
+    ///
+    /// This disables the protection TLS gives against impersonation; prefer
+    /// [`ScraperClientBuilder::add_root_certificate`] where possible.
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.tls.accept_invalid_certs = accept;
+        self
+    }
+
+    /// Refuse to connect with a TLS version older than `version`
+    pub fn min_tls_version(mut self, version: TlsVersion) -> Self {
+        self.tls.min_version = Some(version);
+        self
+    }
+
+    /// Send all requests through this `socks5://` or `socks5h://` proxy
+    pub fn socks5_proxy(mut self, url: &str) -> Self {
+        self.proxy.socks5 = Some(url.to_string());
+        self
+    }
+
+    /// Also use `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` from the environment
+    ///
+    /// Without this flag the client ignores proxy environment variables.
+    pub fn proxy_from_env(mut self, enabled: bool) -> Self {
+        self.proxy.from_env = enabled;
+        self
+    }
+
+    /// Cache fetched pages in `dir` and serve them without a network request for `ttl`
+    ///
+    /// The directory is created by [`ScraperClientBuilder::build`] if it doesn't exist.
+    pub fn disk_cache(mut self, dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        self.disk_cache = Some((dir.into(), ttl));
+        self
+    }
+
+    /// Media types [`ScraperClient::fetch_url`] accepts, replacing the default `text/html` and
+    /// `application/xhtml+xml`
+    ///
+    /// Compared case-insensitively without parameters, so `text/html` matches
+    /// `text/html; charset=utf-8`.
+    pub fn accept_content_types<I, S>(mut self, content_types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.accepted_content_types = content_types.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Archive every successful response under `dir` for later debugging
+    ///
+    /// Bodies go to `<dir>/<host>/<path>-<timestamp>.html` with a `.json` sidecar holding the URL,
+    /// status and headers. The directory is created by [`ScraperClientBuilder::build`].
+    pub fn snapshot_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.snapshot_dir = Some(dir.into());
+        self
+    }
+
+    /// Keep only the newest `keep` snapshots of each URL; by default all are kept
+    pub fn snapshot_retention(mut self, keep: usize) -> Self {
+        self.snapshot_retention = Some(keep);
+        self
+    }
+
+    /// Add a header sent with every request, replacing any earlier value for the same name
+    pub fn default_header(mut self, name: &str, value: &str) -> Self {
+        self.default_headers
+            .push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Allow POST requests to be retried by default; only enable this for endpoints where resubmitting is safe
+    ///
+    /// Individual calls can decide for themselves with [`ScraperClient::post_form_with_retry`].
+    pub fn retry_posts(mut self, enabled: bool) -> Self {
+        self.retry_posts = enabled;
+        self
+    }
+
+    /// How long [`ScraperClient::fetch_first_of`] waits before starting each further mirror
+    ///
+    /// The default of zero starts every mirror at once; a small delay only asks the
+    /// other mirrors when the first one is slow.
+    pub fn hedge_delay(mut self, delay: Duration) -> Self {
+        self.hedge_delay = delay;
+        self
+    }
+
+    /// Use a different clock for retry delays and timings
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Warn when a server's `Date` header differs from the local clock by more than `threshold`
+    pub fn clock_skew_threshold(mut self, threshold: Duration) -> Self {
+        self.clock_skew_threshold = threshold;
+        self
+    }
+
+    /// Largest response body the client will read, in bytes (default 10 MB)
+    ///
+    /// Bigger responses fail with [`ScraperError::ResponseTooLarge`] without being buffered.
+    pub fn max_body_bytes(mut self, limit: u64) -> Self {
+        self.max_body_bytes = limit;
+        self
+    }
+
+    /// How redirects are followed; the default follows up to 10
+    pub fn redirect_policy(mut self, policy: RedirectPolicy) -> Self {
+        self.redirect_policy = policy;
+        self
+    }
+
+    /// Stop sending requests to a host after `threshold` consecutive failures
+    ///
+    /// Requests to an open circuit fail with [`ScraperError::CircuitOpen`] until `cool_down` has
+    /// passed; then a single probe decides whether the circuit closes again. Disabled by default.
+    pub fn circuit_breaker(mut self, threshold: u32, cool_down: Duration) -> Self {
+        self.circuit_breaker = CircuitBreaker::new(threshold, cool_down);
+        self
+    }
+
+    /// Build the client, validating headers and the underlying HTTP client configuration
+    pub fn build(self) -> Result<ScraperClient, ScraperError> {
+        let rate_limiter = RateLimiter::new(self.request_interval()?);
+        let user_agents = self
+            .user_agents
+            .iter()
+            .map(|user_agent| user_agent_value(user_agent))
+            .collect::<Result<Vec<_>, _>>()?;
+        let cookie_jar = self.cookies.then(|| Arc::new(Jar::default()));
+        let client = self.http_client(cookie_jar.clone())?;
+        let disk_cache = match self.disk_cache {
+            Some((dir, ttl)) => {
+                std::fs::create_dir_all(&dir)?;
+                Some(DiskCache::new(dir, ttl))
+            }
+            None => None,
+        };
+        let snapshots = match self.snapshot_dir {
+            Some(dir) => {
+                std::fs::create_dir_all(&dir)?;
+                Some(SnapshotWriter::new(dir, self.snapshot_retention))
+            }
+            None => None,
+        };
+
+        Ok(ScraperClient {
+            client,
+            request_id: AtomicU64::new(0),
+            stats: Mutex::new(ScraperClientStats::default()),
+            max_retries: self.max_retries,
+            request_timeout: self.request_timeout,
+            fetch_deadline: self.fetch_deadline,
+            hedge_delay: self.hedge_delay,
+            backoff: self.backoff,
+            max_retry_after: self.max_retry_after,
+            retry_on_status: self.retry_on_status,
+            rate_limiter: Mutex::new(rate_limiter),
+            circuit_breaker: Mutex::new(self.circuit_breaker),
+            user_agent: self.user_agent,
+            user_agents: UserAgentRotator::new(user_agents, self.user_agent_rotation),
+            respect_robots_txt: self.respect_robots_txt,
+            cookie_jar,
+            robots_cache: Mutex::new(HashMap::new()),
+            response_cache: Mutex::new(ResponseCache::default()),
+            disk_cache,
+            snapshots,
+            accepted_content_types: self.accepted_content_types,
+            hooks: Mutex::new(Hooks::default()),
+            compression: self.compression,
+            retry_posts: self.retry_posts,
+            clock: self.clock,
+            clock_skew_threshold: self.clock_skew_threshold,
+            max_body_bytes: self.max_body_bytes,
+            redirect_policy: self.redirect_policy,
+            last_clock_skew: Mutex::new(None),
+        })
+    }
+
+    fn http_client(&self, cookie_jar: Option<Arc<Jar>>) -> Result<Client, ScraperError> {
+        let mut builder = Client::builder()
+            .default_headers(self.headers()?)
+            .connect_timeout(self.connect_timeout)
+            .timeout(self.request_timeout)
+            .pool_idle_timeout(self.request_timeout)
+            // Redirects are followed by the client itself so it can report the chain
+            .redirect(reqwest::redirect::Policy::none());
+
+        if let Some(cookie_jar) = cookie_jar {
+            builder = builder.cookie_provider(cookie_jar);
+        }
+
+        if self.proxy.is_enabled() {
+            for proxy in self.proxy.proxies(|name| std::env::var(name).ok())? {
+                builder = builder.proxy(proxy);
+            }
+        } else {
+            builder = builder.no_proxy();
+        }
+        Ok(self.tls.apply(builder)?.build()?)
+    }
+
+    /// The spacing between requests implied by both rate-limit settings
+    fn request_interval(&self) -> Result<Duration, ScraperError> {
+        let Some(requests_per_second) = self.requests_per_second else {
+            return Ok(self.min_request_interval);
+        };
+        if !(requests_per_second.is_finite() && requests_per_second > 0.0) {
+            return Err(ScraperError::CustomError(format!(
+                "requests_per_second must be a positive number, got {}",
+                requests_per_second
+            )));
+        }
+        let interval = Duration::from_secs_f64(1.0 / requests_per_second);
+        Ok(interval.max(self.min_request_interval))
+    }
+
+    /// Default headers for the client
+    fn headers(&self) -> Result<HeaderMap, ScraperError> {
+        let mut headers = header_map(
+            self.default_headers
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str())),
+        )?;
+        headers.insert(USER_AGENT, user_agent_value(&self.user_agent)?);
+        headers
+            .entry(ACCEPT_ENCODING)
+            .or_insert_with(|| self.compression.accept_encoding());
+        Ok(headers)
+    }
+}
+
+/// Validate name/value pairs into a `HeaderMap`; a later pair replaces an earlier one with the same name
+pub(super) fn header_map<'a>(
+    pairs: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Result<HeaderMap, ScraperError> {
+    let mut headers = HeaderMap::new();
+    for (name, value) in pairs {
+        let header_name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| ScraperError::InvalidHeader(format!("invalid name {:?}", name)))?;
+        headers.insert(header_name, header_value(name, value)?);
+    }
+    Ok(headers)
+}
+
+/// User-Agents must be plain ASCII; `HeaderValue` alone would accept Latin-1 bytes
+fn user_agent_value(user_agent: &str) -> Result<HeaderValue, ScraperError> {
+    if !user_agent.is_ascii() {
+        return Err(ScraperError::InvalidHeader(format!(
+            "User-Agent must be ASCII: {:?}",
+            user_agent
+        )));
+    }
+    header_value("User-Agent", user_agent)
+}
+
+fn header_value(name: &str, value: &str) -> Result<HeaderValue, ScraperError> {
+    HeaderValue::from_str(value).map_err(|_| {
+        ScraperError::InvalidHeader(format!("invalid value for {}: {:?}", name, value))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_rejects_invalid_header_value() {
+        let result = ScraperClient::builder()
+            .default_header("Accept-Language", "en-AU\r\nX-Injected: 1")
+            .build();
+        assert!(matches!(result, Err(ScraperError::InvalidHeader(_))));
+    }
+
+    #[test]
+    fn test_builder_rejects_invalid_header_name() {
+        let result = ScraperClient::builder()
+            .default_header("Bad Header", "value")
+            .build();
+        assert!(matches!(result, Err(ScraperError::InvalidHeader(_))));
+    }
+
+    #[test]
+    fn test_builder_rejects_invalid_user_agent() {
+        let result = ScraperClient::builder().user_agent("agent\n").build();
+        assert!(matches!(result, Err(ScraperError::InvalidHeader(_))));
+    }
+
+    #[test]
+    fn test_builder_applies_configuration() {
+        let client = ScraperClient::builder()
+            .max_retries(5)
+            .retry_delay(Duration::from_millis(250))
+            .retry_posts(true)
+            .default_header("Accept-Language", "en-AU")
+            .build()
+            .expect("Builder failed");
+
+        assert_eq!(client.max_retries, 5);
+        assert_eq!(
+            client.backoff,
+            BackoffPolicy::Fixed(Duration::from_millis(250))
+        );
+        assert!(client.retry_posts);
+    }
+
+    #[test]
+    fn test_builder_rejects_invalid_requests_per_second() {
+        for requests_per_second in [0.0, -1.0, f64::NAN] {
+            let result = ScraperClient::builder()
+                .requests_per_second(requests_per_second)
+                .build();
+            assert!(matches!(result, Err(ScraperError::CustomError(_))));
+        }
+    }
+
+    #[test]
+    fn test_builder_rejects_non_ascii_rotating_user_agent() {
+        let result = ScraperClient::builder()
+            .user_agents(&["ok/1.0", "caf\u{e9}/1.0"], UserAgentRotation::Random)
+            .build();
+        assert!(matches!(result, Err(ScraperError::InvalidHeader(_))));
+    }
+
+    #[test]
+    fn test_builder_collects_tls_options() {
+        let builder = ScraperClient::builder()
+            .add_root_certificate("first")
+            .add_root_certificate(b"second".to_vec())
+            .danger_accept_invalid_certs(true)
+            .min_tls_version(TlsVersion::TLS_1_2);
+
+        assert_eq!(
+            builder.tls.root_certificates,
+            vec![b"first".to_vec(), b"second".to_vec()]
+        );
+        assert!(builder.tls.accept_invalid_certs);
+        assert_eq!(builder.tls.min_version, Some(TlsVersion::TLS_1_2));
+    }
+
+    #[test]
+    fn test_builder_rejects_bogus_root_certificate() {
+        let result = ScraperClient::builder()
+            .add_root_certificate("not a certificate")
+            .build();
+        assert!(matches!(result, Err(ScraperError::Tls(_))));
+    }
+
+    #[test]
+    fn test_builder_rejects_bad_proxy_url() {
+        let result = ScraperClient::builder()
+            .https_proxy("proxy.corp:3128")
+            .build();
+        assert!(matches!(result, Err(ScraperError::InvalidProxy(_))));
+    }
+}
@@ -0,0 +1,87 @@
+use reqwest::header::{
+    HeaderMap, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use reqwest::RequestBuilder;
+use std::collections::HashMap;
+
+/// Result of a cached fetch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FetchOutcome {
+    /// The server sent a new body
+    Fresh(String),
+    /// The server answered 304 Not Modified and the cached body was reused
+    NotModified(String),
+    /// The body came from the disk cache within its TTL, without a request
+    Cached(String),
+}
+
+impl FetchOutcome {
+    /// The page body, whether fresh or from the cache
+    pub fn body(&self) -> &str {
+        match self {
+            FetchOutcome::Fresh(body)
+            | FetchOutcome::NotModified(body)
+            | FetchOutcome::Cached(body) => body,
+        }
+    }
+
+    pub fn into_body(self) -> String {
+        match self {
+            FetchOutcome::Fresh(body)
+            | FetchOutcome::NotModified(body)
+            | FetchOutcome::Cached(body) => body,
+        }
+    }
+}
+
+/// A response body together with the validators needed to revalidate it
+#[derive(Debug, Clone)]
+pub(crate) struct CachedResponse {
+    pub(crate) body: String,
+    etag: Option<HeaderValue>,
+    last_modified: Option<HeaderValue>,
+}
+
+impl CachedResponse {
+    /// Add `If-None-Match` / `If-Modified-Since` so an unchanged page comes back as a 304
+    pub(crate) fn conditional(&self, mut request: RequestBuilder) -> RequestBuilder {
+        if let Some(etag) = &self.etag {
+            request = request.header(IF_NONE_MATCH, etag.clone());
+        }
+        if let Some(last_modified) = &self.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified.clone());
+        }
+        request
+    }
+}
+
+/// In-memory cache of response bodies keyed by URL
+#[derive(Debug, Default)]
+pub(crate) struct ResponseCache {
+    entries: HashMap<String, CachedResponse>,
+}
+
+impl ResponseCache {
+    pub(crate) fn get(&self, url: &str) -> Option<&CachedResponse> {
+        self.entries.get(url)
+    }
+
+    /// Store a fresh response; one without `ETag` or `Last-Modified` can't be revalidated, so it
+    /// drops any older entry instead
+    pub(crate) fn update(&mut self, url: &str, headers: &HeaderMap, body: &str) {
+        let etag = headers.get(ETAG).cloned();
+        let last_modified = headers.get(LAST_MODIFIED).cloned();
+        if etag.is_none() && last_modified.is_none() {
+            self.entries.remove(url);
+            return;
+        }
+        self.entries.insert(
+            url.to_string(),
+            CachedResponse {
+                body: body.to_string(),
+                etag,
+                last_modified,
+            },
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// State of a host's circuit breaker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests go through normally
+    Closed,
+    /// The host failed too often; requests fail immediately until the cool-down has passed
+    Open,
+    /// The cool-down has passed and one probe request decides whether to close or reopen
+    HalfOpen,
+}
+
+/// Per-host circuit breakers that stop hammering hosts that keep failing
+///
+/// A threshold of 0 disables the breaker.
+#[derive(Debug, Default)]
+pub(crate) struct CircuitBreaker {
+    threshold: u32,
+    cool_down: Duration,
+    hosts: HashMap<String, HostCircuit>,
+}
+
+#[derive(Debug, Default)]
+struct HostCircuit {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    /// When the half-open probe was let through, if one is in flight
+    probe_started: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(threshold: u32, cool_down: Duration) -> Self {
+        Self {
+            threshold,
+            cool_down,
+            ..Self::default()
+        }
+    }
+
+    /// Whether a request to `host` may be sent at `now`; letting one through while half-open
+    /// makes it the probe
+    ///
+    /// A probe that never reports back, e.g. because its future was dropped, is replaced after
+    /// another cool-down.
+    pub(crate) fn allow(&mut self, host: &str, now: Instant) -> bool {
+        let cool_down = self.cool_down;
+        let Some(circuit) = self.hosts.get_mut(host) else {
+            return true;
+        };
+        let Some(opened_at) = circuit.opened_at else {
+            return true;
+        };
+        if now.saturating_duration_since(opened_at) < cool_down {
+            return false;
+        }
+        match circuit.probe_started {
+            Some(started) if now.saturating_duration_since(started) < cool_down => false,
+            _ => {
+                circuit.probe_started = Some(now);
+                true
+            }
+        }
+    }
+
+    /// A request to `host` got an answer from a healthy server: close its circuit
+    pub(crate) fn record_success(&mut self, host: &str) {
+        self.hosts.remove(host);
+    }
+
+    /// A request to `host` failed; returns true when this opened the circuit
+    pub(crate) fn record_failure(&mut self, host: &str, now: Instant) -> bool {
+        if self.threshold == 0 {
+            return false;
+        }
+        let circuit = self.hosts.entry(host.to_string()).or_default();
+        if circuit.probe_started.take().is_some() {
+            // The probe failed, so wait out another cool-down
+            circuit.opened_at = Some(now);
+            return false;
+        }
+        circuit.consecutive_failures += 1;
+        if circuit.opened_at.is_none() && circuit.consecutive_failures >= self.threshold {
+            circuit.opened_at = Some(now);
+            return true;
+        }
+        false
+    }
+
+    pub(crate) fn state(&self, host: &str, now: Instant) -> CircuitState {
+        let Some(opened_at) = self.hosts.get(host).and_then(|circuit| circuit.opened_at) else {
+            return CircuitState::Closed;
+        };
+        if now.saturating_duration_since(opened_at) < self.cool_down {
+            CircuitState::Open
+        } else {
+            CircuitState::HalfOpen
+        }
+    }
+
+    /// Hosts whose circuit is currently open or half-open
+    pub(crate) fn tripped_hosts(&self) -> Vec<&str> {
+        let mut hosts: Vec<&str> = self
+            .hosts
+            .iter()
+            .filter(|(_, circuit)| circuit.opened_at.is_some())
+            .map(|(host, _)| host.as_str())
+            .collect();
+        hosts.sort_unstable();
+        hosts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, MockClock};
+
+    const HOST: &str = "www.commerce.wa.gov.au";
+
+    #[test]
+    fn test_circuit_state_transitions() {
+        let clock = MockClock::default();
+        let mut breaker = CircuitBreaker::new(3, Duration::from_secs(30));
+
+        for _ in 0..2 {
+            breaker.record_failure(HOST, clock.now_instant());
+        }
+        assert_eq!(
+            breaker.state(HOST, clock.now_instant()),
+            CircuitState::Closed
+        );
+        assert!(breaker.allow(HOST, clock.now_instant()));
+
+        assert!(breaker.record_failure(HOST, clock.now_instant()));
+        assert_eq!(breaker.state(HOST, clock.now_instant()), CircuitState::Open);
+        assert!(!breaker.allow(HOST, clock.now_instant()));
+        assert!(breaker.allow("other.example", clock.now_instant()));
+
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(
+            breaker.state(HOST, clock.now_instant()),
+            CircuitState::HalfOpen
+        );
+        assert!(breaker.allow(HOST, clock.now_instant()));
+        // Only the probe gets through
+        assert!(!breaker.allow(HOST, clock.now_instant()));
+
+        breaker.record_success(HOST);
+        assert_eq!(
+            breaker.state(HOST, clock.now_instant()),
+            CircuitState::Closed
+        );
+        assert!(breaker.allow(HOST, clock.now_instant()));
+    }
+
+    #[test]
+    fn test_failed_probe_reopens_circuit() {
+        let clock = MockClock::default();
+        let mut breaker = CircuitBreaker::new(1, Duration::from_secs(30));
+        breaker.record_failure(HOST, clock.now_instant());
+
+        clock.advance(Duration::from_secs(30));
+        assert!(breaker.allow(HOST, clock.now_instant()));
+        breaker.record_failure(HOST, clock.now_instant());
+
+        assert_eq!(breaker.state(HOST, clock.now_instant()), CircuitState::Open);
+        clock.advance(Duration::from_secs(29));
+        assert!(!breaker.allow(HOST, clock.now_instant()));
+        clock.advance(Duration::from_secs(1));
+        assert!(breaker.allow(HOST, clock.now_instant()));
+    }
+
+    #[test]
+    fn test_abandoned_probe_is_replaced() {
+        let clock = MockClock::default();
+        let mut breaker = CircuitBreaker::new(1, Duration::from_secs(30));
+        breaker.record_failure(HOST, clock.now_instant());
+
+        clock.advance(Duration::from_secs(30));
+        assert!(breaker.allow(HOST, clock.now_instant()));
+        clock.advance(Duration::from_secs(30));
+        assert!(breaker.allow(HOST, clock.now_instant()));
+    }
+
+    #[test]
+    fn test_disabled_breaker_never_opens() {
+        let mut breaker = CircuitBreaker::default();
+        let now = Instant::now();
+        for _ in 0..100 {
+            breaker.record_failure(HOST, now);
+        }
+        assert!(breaker.allow(HOST, now));
+        assert!(breaker.tripped_hosts().is_empty());
+    }
+}
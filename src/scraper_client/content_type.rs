@@ -0,0 +1,102 @@
+use super::FetchResponse;
+use crate::errors::ScraperError;
+
+/// Media types [`super::ScraperClient::fetch_url`] accepts unless configured otherwise
+pub(crate) const DEFAULT_ACCEPTED: &[&str] = &["text/html", "application/xhtml+xml"];
+
+/// Fail with `UnexpectedContentType` unless the response looks like one of the `accepted` types
+///
+/// Parameters such as `charset` are ignored. Without a `Content-Type` header the body is
+/// sniffed instead: anything starting with `<` after whitespace passes as markup.
+pub(crate) fn check(response: &FetchResponse, accepted: &[String]) -> Result<(), ScraperError> {
+    let got = match response.content_type() {
+        Some(content_type) => essence(content_type),
+        None if looks_like_markup(&response.body) => return Ok(()),
+        None => "no Content-Type and a body that isn't markup".to_string(),
+    };
+    if accepted
+        .iter()
+        .any(|accepted| accepted.eq_ignore_ascii_case(&got))
+    {
+        return Ok(());
+    }
+    Err(ScraperError::UnexpectedContentType {
+        expected: accepted.join(", "),
+        got,
+        url: response.final_url.to_string(),
+    })
+}
+
+/// `text/html; charset=utf-8` -> `text/html`
+fn essence(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+fn looks_like_markup(body: &str) -> bool {
+    body.trim_start_matches('\u{feff}')
+        .trim_start()
+        .starts_with('<')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+    use reqwest::Url;
+    use std::time::{Duration, SystemTime};
+
+    fn response(content_type: Option<&'static str>, body: &str) -> FetchResponse {
+        let mut headers = HeaderMap::new();
+        if let Some(content_type) = content_type {
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+        }
+        FetchResponse {
+            body: body.to_string(),
+            status: 200,
+            headers,
+            final_url: Url::parse("https://example.com/holidays").unwrap(),
+            redirects: Vec::new(),
+            encoding: "UTF-8",
+            attempts: 1,
+            elapsed: Duration::ZERO,
+            fetched_at: SystemTime::UNIX_EPOCH,
+            transfer: Default::default(),
+        }
+    }
+
+    fn html_only() -> Vec<String> {
+        vec!["text/html".to_string()]
+    }
+
+    #[test]
+    fn test_check_ignores_parameters_and_case() {
+        let response = response(Some("Text/HTML; charset=windows-1252"), "");
+        assert!(check(&response, &html_only()).is_ok());
+    }
+
+    #[test]
+    fn test_check_rejects_other_types() {
+        let err = check(&response(Some("application/pdf"), "%PDF-1.7"), &html_only()).unwrap_err();
+        match err {
+            ScraperError::UnexpectedContentType { expected, got, url } => {
+                assert_eq!(expected, "text/html");
+                assert_eq!(got, "application/pdf");
+                assert_eq!(url, "https://example.com/holidays");
+            }
+            other => panic!("expected UnexpectedContentType, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_check_sniffs_body_without_header() {
+        assert!(check(&response(None, "\u{feff}\n  <!DOCTYPE html>"), &html_only()).is_ok());
+        assert!(check(&response(None, "<table></table>"), &html_only()).is_ok());
+        assert!(check(&response(None, "%PDF-1.7"), &html_only()).is_err());
+        assert!(check(&response(None, "{\"holidays\": []}"), &html_only()).is_err());
+    }
+}
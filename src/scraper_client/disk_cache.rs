@@ -0,0 +1,177 @@
+use reqwest::header::HeaderMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Page bodies stored on disk, one file per URL plus a metadata sidecar
+///
+/// Files are named by a hash of the URL: `<hash>.body` holds the body and `<hash>.meta` holds
+/// the URL, fetch time, status and response headers as `key: value` lines.
+#[derive(Debug)]
+pub(crate) struct DiskCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl DiskCache {
+    pub(crate) fn new(dir: PathBuf, ttl: Duration) -> Self {
+        Self { dir, ttl }
+    }
+
+    /// The cached body for `url` if it's younger than the TTL at `now`
+    pub(crate) fn load(&self, url: &str, now: SystemTime) -> io::Result<Option<String>> {
+        let (body_path, meta_path) = self.paths(url);
+        let meta = match fs::read_to_string(&meta_path) {
+            Ok(meta) => meta,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let mut cached_url = None;
+        let mut fetched_at = None;
+        for line in meta.lines() {
+            match line.split_once(": ") {
+                Some(("url", value)) => cached_url = Some(value),
+                Some(("fetched_at", value)) => fetched_at = value.parse::<u64>().ok(),
+                _ => {}
+            }
+        }
+        // Guard against hash collisions and unreadable sidecars
+        let (Some(cached_url), Some(fetched_at)) = (cached_url, fetched_at) else {
+            return Ok(None);
+        };
+        if cached_url != url {
+            return Ok(None);
+        }
+        let fetched_at = SystemTime::UNIX_EPOCH + Duration::from_secs(fetched_at);
+        let age = now.duration_since(fetched_at).unwrap_or_default();
+        if age >= self.ttl {
+            return Ok(None);
+        }
+
+        match fs::read_to_string(&body_path) {
+            Ok(body) => Ok(Some(body)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Write the body and metadata for `url`, replacing any earlier entry
+    pub(crate) fn store(
+        &self,
+        url: &str,
+        status: u16,
+        headers: &HeaderMap,
+        body: &str,
+        now: SystemTime,
+    ) -> io::Result<()> {
+        let fetched_at = now
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut meta = format!(
+            "url: {}\nfetched_at: {}\nstatus: {}\n",
+            url, fetched_at, status
+        );
+        for (name, value) in headers {
+            if let Ok(value) = value.to_str() {
+                meta.push_str(&format!("header: {}: {}\n", name, value));
+            }
+        }
+
+        let (body_path, meta_path) = self.paths(url);
+        // Body first so a sidecar never points at a missing or stale body
+        fs::write(body_path, body)?;
+        fs::write(meta_path, meta)
+    }
+
+    /// Remove every cache file from the directory, leaving other files alone
+    pub(crate) fn clear(&self) -> io::Result<()> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if is_cache_file(&path) {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn paths(&self, url: &str) -> (PathBuf, PathBuf) {
+        let name = format!("{:016x}", fnv1a(url.as_bytes()));
+        (
+            self.dir.join(format!("{}.body", name)),
+            self.dir.join(format!("{}.meta", name)),
+        )
+    }
+}
+
+fn is_cache_file(path: &Path) -> bool {
+    let is_hash = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| stem.len() == 16 && stem.chars().all(|c| c.is_ascii_hexdigit()));
+    let extension = path.extension().and_then(|extension| extension.to_str());
+    is_hash && matches!(extension, Some("body" | "meta"))
+}
+
+/// 64-bit FNV-1a, used because it's stable across Rust versions unlike `DefaultHasher`
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderValue, ETAG};
+
+    const URL: &str = "https://example.com/holidays";
+
+    #[test]
+    fn test_disk_cache_round_trip_and_expiry() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = DiskCache::new(dir.path().to_path_buf(), Duration::from_secs(60));
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, HeaderValue::from_static("\"v1\""));
+
+        assert_eq!(cache.load(URL, now).unwrap(), None);
+        cache.store(URL, 200, &headers, "holidays", now).unwrap();
+
+        let meta = fs::read_to_string(cache.paths(URL).1).unwrap();
+        assert!(meta.contains("status: 200\n"), "{}", meta);
+        assert!(meta.contains("header: etag: \"v1\"\n"), "{}", meta);
+
+        let later = now + Duration::from_secs(59);
+        assert_eq!(cache.load(URL, later).unwrap().as_deref(), Some("holidays"));
+        assert_eq!(
+            cache.load(URL, now + Duration::from_secs(60)).unwrap(),
+            None
+        );
+        assert_eq!(cache.load("https://example.com/other", now).unwrap(), None);
+    }
+
+    #[test]
+    fn test_disk_cache_clear_only_removes_cache_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = DiskCache::new(dir.path().to_path_buf(), Duration::from_secs(60));
+        let now = SystemTime::UNIX_EPOCH;
+        cache
+            .store(URL, 200, &HeaderMap::new(), "holidays", now)
+            .unwrap();
+        fs::write(dir.path().join("notes.txt"), "keep me").unwrap();
+
+        cache.clear().unwrap();
+
+        assert_eq!(cache.load(URL, now).unwrap(), None);
+        let remaining: Vec<_> = fs::read_dir(dir.path()).unwrap().collect();
+        assert_eq!(remaining.len(), 1);
+    }
+}
@@ -0,0 +1,59 @@
+use crate::errors::ScraperError;
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode, Url};
+use std::ops::ControlFlow;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The parts of an outgoing request a request hook may inspect or change
+#[derive(Debug, Clone)]
+pub struct RequestParts {
+    /// Id shared by every attempt of one call, as in the logs
+    pub request_id: u64,
+    /// 1 for the first attempt, 2 for the first retry and so on
+    pub attempt: u8,
+    pub method: Method,
+    /// Where the request goes; rewriting it also moves rate limiting and robots checks
+    pub url: Url,
+    /// Headers set for this request; the client's defaults are added afterwards where missing
+    pub headers: HeaderMap,
+}
+
+/// What came back for one attempt, as seen by response hooks
+#[derive(Debug, Clone, Copy)]
+pub struct ResponseParts<'a> {
+    pub request_id: u64,
+    pub attempt: u8,
+    pub method: &'a Method,
+    /// The URL that answered, after any redirects
+    pub url: &'a Url,
+    pub status: StatusCode,
+    pub headers: &'a HeaderMap,
+    /// Time from sending the attempt until its response headers arrived
+    pub elapsed: Duration,
+}
+
+/// A failed attempt, as reported to [`super::ScraperClient::on_retry`] callbacks
+#[derive(Debug, Clone, Copy)]
+pub struct RetryEvent<'a> {
+    pub request_id: u64,
+    /// The URL the attempt was sent to, after request hooks
+    pub url: &'a Url,
+    pub attempt: u8,
+    /// Why the attempt failed; an unwanted status is an `HttpStatus`
+    pub error: &'a ScraperError,
+    /// How long the client will wait before the next attempt, or `None` if this was the last
+    pub delay: Option<Duration>,
+}
+
+pub(crate) type RequestHook = Arc<dyn Fn(&mut RequestParts) + Send + Sync>;
+pub(crate) type ResponseHook = Arc<dyn Fn(&ResponseParts<'_>) + Send + Sync>;
+pub(crate) type RetryCallback = Arc<dyn Fn(&RetryEvent<'_>) -> ControlFlow<()> + Send + Sync>;
+
+/// Hooks registered on a client, run in the order they were added
+#[derive(Default)]
+pub(crate) struct Hooks {
+    pub(crate) request: Vec<RequestHook>,
+    pub(crate) response: Vec<ResponseHook>,
+    pub(crate) retry: Vec<RetryCallback>,
+}
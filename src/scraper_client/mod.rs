@@ -0,0 +1,3819 @@
+mod backoff;
+mod body;
+mod builder;
+mod cache;
+mod circuit_breaker;
+mod content_type;
+mod disk_cache;
+mod hooks;
+mod proxy;
+mod rate_limit;
+mod redirect;
+mod response;
+mod retry;
+mod robots;
+mod snapshot;
+mod stats;
+mod tls;
+mod user_agent;
+mod watch;
+
+pub use backoff::{parse_retry_after, BackoffPolicy};
+pub use body::Compression;
+pub use builder::ScraperClientBuilder;
+pub use cache::FetchOutcome;
+pub use circuit_breaker::CircuitState;
+pub use hooks::{RequestParts, ResponseParts, RetryEvent};
+pub use redirect::{Redirect, RedirectPolicy};
+pub use reqwest::tls::Version as TlsVersion;
+pub use response::{FetchResponse, HeadResponse, Transfer};
+pub use retry::{is_retryable_status, RetryPredicate};
+pub use stats::{HostStats, LatencySummary, StatsSnapshot};
+pub use user_agent::UserAgentRotation;
+pub use watch::ContentChange;
+
+use crate::clock::Clock;
+use crate::errors::{ScraperError, TimeoutPhase};
+use cache::ResponseCache;
+use circuit_breaker::CircuitBreaker;
+use disk_cache::DiskCache;
+use futures::stream::{self, FuturesUnordered, StreamExt};
+use hooks::Hooks;
+use log::{debug, error, info, warn};
+use rate_limit::RateLimiter;
+use reqwest::cookie::{CookieStore, Jar};
+use reqwest::header::{HeaderValue, DATE, LOCATION, RETRY_AFTER, USER_AGENT};
+use reqwest::{Client, IntoUrl, Method, Request, RequestBuilder, Response, StatusCode, Url};
+use robots::RobotsRules;
+use serde::Serialize;
+use snapshot::SnapshotWriter;
+use stats::ScraperClientStats;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::OnceCell;
+use user_agent::UserAgentRotator;
+
+pub struct ScraperClient {
+    client: Client,
+    request_id: AtomicU64,
+    stats: Mutex<ScraperClientStats>,
+    max_retries: u8,
+    request_timeout: Duration,
+    fetch_deadline: Option<Duration>,
+    hedge_delay: Duration,
+    backoff: BackoffPolicy,
+    max_retry_after: Duration,
+    retry_on_status: RetryPredicate,
+    rate_limiter: Mutex<RateLimiter>,
+    circuit_breaker: Mutex<CircuitBreaker>,
+    user_agent: String,
+    user_agents: UserAgentRotator,
+    respect_robots_txt: bool,
+    cookie_jar: Option<Arc<Jar>>,
+    robots_cache: Mutex<HashMap<String, Arc<OnceCell<RobotsRules>>>>,
+    response_cache: Mutex<ResponseCache>,
+    disk_cache: Option<DiskCache>,
+    snapshots: Option<SnapshotWriter>,
+    accepted_content_types: Vec<String>,
+    hooks: Mutex<Hooks>,
+    compression: Compression,
+    retry_posts: bool,
+    clock: Arc<dyn Clock>,
+    clock_skew_threshold: Duration,
+    max_body_bytes: u64,
+    redirect_policy: RedirectPolicy,
+    last_clock_skew: Mutex<Option<ClockSkew>>,
+}
+
+/// Difference between a server's `Date` header and the local clock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockSkew {
+    /// The server clock is ahead of the local clock by this much
+    ServerAhead(Duration),
+    /// The server clock is behind the local clock by this much
+    ServerBehind(Duration),
+}
+
+impl ClockSkew {
+    /// Size of the skew regardless of direction
+    pub fn magnitude(&self) -> Duration {
+        match self {
+            ClockSkew::ServerAhead(delta) | ClockSkew::ServerBehind(delta) => *delta,
+        }
+    }
+}
+
+/// How much of an error response's body `HttpStatus` keeps
+const BODY_SNIPPET_CHARS: usize = 200;
+
+/// How many times, and for how long, a call may keep retrying
+#[derive(Debug, Clone, Copy)]
+struct RetryBudget {
+    max_retries: u8,
+    /// Total time allowed from the first attempt, retry delays included
+    deadline: Option<Duration>,
+}
+
+/// How `send_attempts` reads the body of a successful response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReadBody {
+    /// Decode it into `FetchResponse::body`
+    Text,
+    /// Return the raw bytes alongside the response, leaving `FetchResponse::body` empty
+    Bytes,
+    /// Leave it unread, as for HEAD
+    Skip,
+}
+
+/// What a call to `send_attempts` got through, for the stats
+#[derive(Default)]
+struct Progress {
+    /// Set once a request has been built
+    method: Option<Method>,
+    host: Option<String>,
+    attempts: u8,
+    /// Set once a successful response's body has been read
+    transfer: Option<Transfer>,
+    attempt_latencies: Vec<Duration>,
+}
+
+impl ScraperClient {
+    /// Create a new scraper client with default timeout and retry configuration
+    pub fn new_http() -> Self {
+        ScraperClientBuilder::default()
+            .build()
+            .expect("Failed to build HTTP client")
+    }
+
+    /// Start configuring a scraper client
+    pub fn builder() -> ScraperClientBuilder {
+        ScraperClientBuilder::default()
+    }
+
+    /// Run `hook` on every request attempt just before it's sent, including retries
+    ///
+    /// The hook can change the URL and headers, e.g. to sign requests or point them at a mirror.
+    /// Hooks run in the order they were added, each seeing the changes of the ones before.
+    pub fn add_request_hook<F>(&self, hook: F)
+    where
+        F: Fn(&mut RequestParts) + Send + Sync + 'static,
+    {
+        self.hooks.lock().unwrap().request.push(Arc::new(hook));
+    }
+
+    /// Run `hook` on every response received, including the failed attempts that get retried
+    ///
+    /// Attempts that fail without a response, such as timeouts, don't reach response hooks.
+    pub fn add_response_hook<F>(&self, hook: F)
+    where
+        F: Fn(&ResponseParts<'_>) + Send + Sync + 'static,
+    {
+        self.hooks.lock().unwrap().response.push(Arc::new(hook));
+    }
+
+    /// Call `callback` for every failed attempt, including the last one
+    ///
+    /// Returning [`ControlFlow::Break`] stops retrying; the call then fails with
+    /// [`ScraperError::RetriesExhausted`] holding that attempt's error.
+    pub fn on_retry<F>(&self, callback: F)
+    where
+        F: Fn(&RetryEvent<'_>) -> ControlFlow<()> + Send + Sync + 'static,
+    {
+        self.hooks.lock().unwrap().retry.push(Arc::new(callback));
+    }
+
+    /// Clock skew measured from the most recent response that carried a valid `Date` header
+    pub fn last_clock_skew(&self) -> Option<ClockSkew> {
+        *self.last_clock_skew.lock().unwrap()
+    }
+
+    /// Asynchronously fetch the content of the web page with retry logic
+    ///
+    /// Bodies that aren't HTML, or another type allowed by
+    /// [`ScraperClientBuilder::accept_content_types`], fail with
+    /// [`ScraperError::UnexpectedContentType`].
+    /// Cancel-safe: stats are only updated once a request has finished, so dropping the future
+    /// mid-fetch leaves them untouched.
+    pub async fn fetch_url<U: Copy + IntoUrl>(&self, url: U) -> Result<String, ScraperError> {
+        self.fetch_url_cached(url)
+            .await
+            .map(FetchOutcome::into_body)
+    }
+
+    /// Like [`ScraperClient::fetch_url`], but returning the body whatever its `Content-Type`
+    pub async fn fetch_url_unchecked<U: IntoUrl>(&self, url: U) -> Result<String, ScraperError> {
+        self.fetch_url_cached_with(url, self.fetch_deadline, false, |request| request)
+            .await
+            .map(FetchOutcome::into_body)
+    }
+
+    /// Like [`ScraperClient::fetch_url`], but each attempt may take up to `timeout` instead of the
+    /// client's request timeout
+    pub async fn fetch_url_with_timeout<U: IntoUrl>(
+        &self,
+        url: U,
+        timeout: Duration,
+    ) -> Result<String, ScraperError> {
+        self.fetch_url_cached_with(url, self.fetch_deadline, true, |request| {
+            request.timeout(timeout)
+        })
+        .await
+        .map(FetchOutcome::into_body)
+    }
+
+    /// Like [`ScraperClient::fetch_url`], but giving up once `deadline` has passed since the first
+    /// attempt instead of after the client's fetch deadline
+    pub async fn fetch_url_with_deadline<U: IntoUrl>(
+        &self,
+        url: U,
+        deadline: Duration,
+    ) -> Result<String, ScraperError> {
+        self.fetch_url_cached_with(url, Some(deadline), true, |request| request)
+            .await
+            .map(FetchOutcome::into_body)
+    }
+
+    /// Like [`ScraperClient::fetch_url`], with extra headers for this request only
+    ///
+    /// The headers are merged over the client's defaults, winning where both set the same name,
+    /// so e.g. a `User-Agent` here replaces the configured or rotated one. Invalid names or values
+    /// fail with [`ScraperError::InvalidHeader`] before anything is sent.
+    pub async fn fetch_url_with_headers<U: IntoUrl>(
+        &self,
+        url: U,
+        headers: &[(&str, &str)],
+    ) -> Result<String, ScraperError> {
+        let headers = builder::header_map(headers.iter().copied())?;
+        self.fetch_url_cached_with(url, self.fetch_deadline, true, |request| {
+            request.headers(headers.clone())
+        })
+        .await
+        .map(FetchOutcome::into_body)
+    }
+
+    /// Fetch a page with its status, headers, final URL and timing
+    ///
+    /// Unlike [`ScraperClient::fetch_url`] this always makes a request: cached copies are neither
+    /// used nor revalidated, though a fresh body still updates the caches. The `Content-Type` is
+    /// checked in the same way.
+    pub async fn fetch<U: IntoUrl>(&self, url: U) -> Result<FetchResponse, ScraperError> {
+        let url = url.into_url()?;
+        let response = self
+            .send_with_retries(
+                |client| client.get(url.clone()),
+                self.budget(self.max_retries),
+            )
+            .await?;
+        content_type::check(&response, &self.accepted_content_types)?;
+        let key = url.to_string();
+        self.response_cache
+            .lock()
+            .unwrap()
+            .update(&key, &response.headers, &response.body);
+        self.store_on_disk(&key, &response, &response.body);
+        Ok(response)
+    }
+
+    /// Refetch a page every `interval` until its body changes, making at most `max_polls` fetches
+    ///
+    /// Returns `None` if the body never changed. Every poll is a fresh request through the usual
+    /// retries and rate limiting. Cancel-safe in the same way as [`ScraperClient::fetch_url`], so
+    /// dropping the future stops polling.
+    pub async fn poll_until_changed<U: IntoUrl>(
+        &self,
+        url: U,
+        interval: Duration,
+        max_polls: u32,
+    ) -> Result<Option<ContentChange>, ScraperError> {
+        self.poll_until_changed_with(url, interval, max_polls, str::to_string)
+            .await
+    }
+
+    /// [`ScraperClient::poll_until_changed`], comparing bodies after `normalise`
+    ///
+    /// Use this to strip parts of the page that change on every request, such as timestamps.
+    pub async fn poll_until_changed_with<U, N>(
+        &self,
+        url: U,
+        interval: Duration,
+        max_polls: u32,
+        normalise: N,
+    ) -> Result<Option<ContentChange>, ScraperError>
+    where
+        U: IntoUrl,
+        N: Fn(&str) -> String,
+    {
+        let url = url.into_url()?;
+        let mut previous: Option<(String, u64)> = None;
+
+        for poll in 1..=max_polls {
+            if previous.is_some() {
+                self.clock.sleep(interval).await;
+            }
+            let body = self.fetch(url.clone()).await?.body;
+            let hash = watch::body_hash(&normalise(&body));
+            match previous {
+                Some((old_body, old_hash)) if old_hash != hash => {
+                    info!("{} changed after {} polls", url, poll);
+                    return Ok(Some(ContentChange {
+                        old_body,
+                        new_body: body,
+                        polls: poll,
+                    }));
+                }
+                _ => debug!("{} unchanged after {} polls", url, poll),
+            }
+            previous = Some((body, hash));
+        }
+        Ok(None)
+    }
+
+    /// Send a HEAD request, returning just the status, headers and final URL
+    ///
+    /// Useful for checking `Last-Modified` or `Content-Length` before committing to a download.
+    /// Retried, rate limited and counted in the stats like [`ScraperClient::fetch_url`].
+    pub async fn head<U: IntoUrl>(&self, url: U) -> Result<HeadResponse, ScraperError> {
+        let url = url.into_url()?;
+        let (response, _) = self
+            .send_reading(
+                |client| client.head(url.clone()),
+                self.budget(self.max_retries),
+                ReadBody::Skip,
+            )
+            .await?;
+        Ok(HeadResponse {
+            status: response.status,
+            headers: response.headers,
+            final_url: response.final_url,
+        })
+    }
+
+    /// Fetch a resource that isn't text, such as a PDF or ICS download, as raw bytes
+    ///
+    /// Bodies over [`ScraperClientBuilder::max_body_bytes`] fail with
+    /// [`ScraperError::ResponseTooLarge`]. The `Content-Type` isn't checked and nothing is cached.
+    pub async fn fetch_bytes<U: IntoUrl>(&self, url: U) -> Result<Vec<u8>, ScraperError> {
+        let url = url.into_url()?;
+        let (_, bytes) = self
+            .send_reading(
+                |client| client.get(url.clone()),
+                self.budget(self.max_retries),
+                ReadBody::Bytes,
+            )
+            .await?;
+        Ok(bytes)
+    }
+
+    /// Fetch several pages concurrently, with at most `max_concurrency` requests in flight
+    ///
+    /// Each URL gets the usual retries, rate limiting and stats; one failure doesn't stop the others.
+    /// Results come back in the same order as `urls`, each paired with its URL.
+    pub async fn fetch_urls<U: IntoUrl + Clone + Display>(
+        &self,
+        urls: &[U],
+        max_concurrency: usize,
+    ) -> Vec<(String, Result<String, ScraperError>)> {
+        stream::iter(urls)
+            .map(|url| async move {
+                let result = self
+                    .fetch_url_cached(url.clone())
+                    .await
+                    .map(FetchOutcome::into_body);
+                (url.to_string(), result)
+            })
+            .buffered(max_concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Fetch the same page from several mirrors and return the first body to arrive, with its URL
+    ///
+    /// Mirrors are started in order, [`ScraperClientBuilder::hedge_delay`] apart. Once one succeeds
+    /// the others are dropped; those already sent count as cancelled rather than failed.
+    /// If every mirror fails, the error is a `PartialFailure` listing each URL's error.
+    pub async fn fetch_first_of<U: IntoUrl + Clone + Display>(
+        &self,
+        urls: &[U],
+    ) -> Result<(String, String), ScraperError> {
+        if urls.is_empty() {
+            return Err(ScraperError::CustomError(
+                "No mirror URLs to fetch from".to_string(),
+            ));
+        }
+        let started: Vec<AtomicBool> = urls.iter().map(|_| AtomicBool::new(false)).collect();
+        let mut finished = vec![false; urls.len()];
+        let mut pending: FuturesUnordered<_> = urls
+            .iter()
+            .zip(&started)
+            .enumerate()
+            .map(|(index, (url, started))| async move {
+                let delay = self.hedge_delay * index as u32;
+                if !delay.is_zero() {
+                    self.clock.sleep(delay).await;
+                }
+                started.store(true, Ordering::Relaxed);
+                let result = self
+                    .fetch_url_cached(url.clone())
+                    .await
+                    .map(FetchOutcome::into_body);
+                (index, result)
+            })
+            .collect();
+
+        let mut errors = Vec::new();
+        while let Some((index, result)) = pending.next().await {
+            finished[index] = true;
+            match result {
+                Ok(body) => {
+                    drop(pending);
+                    let cancelled = (0..urls.len())
+                        .filter(|&i| started[i].load(Ordering::Relaxed) && !finished[i])
+                        .count();
+                    self.stats_mut().totals.cancelled_requests += cancelled as u64;
+                    debug!(
+                        "{} answered first, cancelled {} other mirrors",
+                        urls[index], cancelled
+                    );
+                    return Ok((body, urls[index].to_string()));
+                }
+                Err(err) => {
+                    warn!("Mirror {} failed: {}", urls[index], err);
+                    errors.push((index, err));
+                }
+            }
+        }
+
+        errors.sort_by_key(|(index, _)| *index);
+        Err(ScraperError::PartialFailure {
+            succeeded: 0,
+            errors: errors
+                .into_iter()
+                .map(|(index, err)| (urls[index].to_string(), err))
+                .collect(),
+        })
+    }
+
+    /// Fetch a page, revalidating any cached copy with `If-None-Match` / `If-Modified-Since`
+    ///
+    /// Bodies served with an `ETag` or `Last-Modified` header are kept in memory for the lifetime
+    /// of the client; a 304 reuses the cached body and counts as a cache hit.
+    /// With [`ScraperClientBuilder::disk_cache`], pages younger than the TTL are read from disk
+    /// without any request.
+    pub async fn fetch_url_cached<U: IntoUrl>(&self, url: U) -> Result<FetchOutcome, ScraperError> {
+        self.fetch_url_cached_with(url, self.fetch_deadline, true, |request| request)
+            .await
+    }
+
+    /// [`ScraperClient::fetch_url_cached`] with per-call changes applied to each GET request
+    ///
+    /// Fresh bodies are checked against the accepted content types if `check_content_type` is set.
+    async fn fetch_url_cached_with<U, F>(
+        &self,
+        url: U,
+        deadline: Option<Duration>,
+        check_content_type: bool,
+        customize: F,
+    ) -> Result<FetchOutcome, ScraperError>
+    where
+        U: IntoUrl,
+        F: Fn(RequestBuilder) -> RequestBuilder,
+    {
+        let url = url.into_url()?;
+        let key = url.to_string();
+        if let Some(body) = self.load_from_disk(&key) {
+            return Ok(FetchOutcome::Cached(body));
+        }
+        let cached = self.response_cache.lock().unwrap().get(&key).cloned();
+
+        let fetched = self
+            .send_with_retries(
+                |client| {
+                    let request = customize(client.get(url.clone()));
+                    match &cached {
+                        Some(cached) => cached.conditional(request),
+                        None => request,
+                    }
+                },
+                RetryBudget {
+                    max_retries: self.max_retries,
+                    deadline,
+                },
+            )
+            .await;
+        // Counted once the request has finished, like the other stats
+        if self.disk_cache.is_some() {
+            self.stats_mut().totals.cache_misses += 1;
+        }
+        let fetched = fetched?;
+
+        if fetched.status == StatusCode::NOT_MODIFIED.as_u16() {
+            return match cached {
+                Some(cached) => {
+                    self.stats_mut().totals.cache_hits += 1;
+                    info!("{} not modified, using cached body", key);
+                    self.store_on_disk(&key, &fetched, &cached.body);
+                    Ok(FetchOutcome::NotModified(cached.body))
+                }
+                None => Err(ScraperError::CustomError(format!(
+                    "{} returned 304 Not Modified but nothing is cached",
+                    key
+                ))),
+            };
+        }
+        if check_content_type {
+            content_type::check(&fetched, &self.accepted_content_types)?;
+        }
+        self.response_cache
+            .lock()
+            .unwrap()
+            .update(&key, &fetched.headers, &fetched.body);
+        self.store_on_disk(&key, &fetched, &fetched.body);
+        Ok(FetchOutcome::Fresh(fetched.body))
+    }
+
+    /// Add a cookie that will be sent with requests to `url`, e.g. a session copied from a browser
+    ///
+    /// Fails unless cookies were enabled with [`ScraperClientBuilder::with_cookies`].
+    pub fn add_cookie(&self, url: &str, name: &str, value: &str) -> Result<(), ScraperError> {
+        let cookie_jar = self.cookie_jar()?;
+        let url =
+            Url::parse(url).map_err(|e| ScraperError::CustomError(format!("{}: {}", url, e)))?;
+        cookie_jar.add_cookie_str(&format!("{}={}", name, value), &url);
+        Ok(())
+    }
+
+    /// The name/value pairs the cookie jar would send to `url`, for debugging sessions
+    pub fn cookies(&self, url: &str) -> Result<Vec<(String, String)>, ScraperError> {
+        let cookie_jar = self.cookie_jar()?;
+        let url =
+            Url::parse(url).map_err(|e| ScraperError::CustomError(format!("{}: {}", url, e)))?;
+        let Some(header) = cookie_jar.cookies(&url) else {
+            return Ok(Vec::new());
+        };
+        let header = header
+            .to_str()
+            .map_err(|e| ScraperError::InvalidHeader(e.to_string()))?;
+        Ok(header
+            .split("; ")
+            .filter_map(|cookie| cookie.split_once('='))
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect())
+    }
+
+    fn cookie_jar(&self) -> Result<&Jar, ScraperError> {
+        self.cookie_jar.as_deref().ok_or_else(|| {
+            ScraperError::CustomError("cookies are not enabled on this client".to_string())
+        })
+    }
+
+    /// Delete every page in the disk cache and forget the in-memory validators
+    pub fn cache_clear(&self) -> Result<(), ScraperError> {
+        *self.response_cache.lock().unwrap() = ResponseCache::default();
+        if let Some(disk_cache) = &self.disk_cache {
+            disk_cache.clear()?;
+        }
+        Ok(())
+    }
+
+    /// A still-fresh body from the disk cache; read errors are logged and treated as a miss
+    fn load_from_disk(&self, url: &str) -> Option<String> {
+        let disk_cache = self.disk_cache.as_ref()?;
+        match disk_cache.load(url, self.clock.now_utc()) {
+            Ok(Some(body)) => {
+                self.stats_mut().totals.cache_hits += 1;
+                info!("Serving {} from disk cache", url);
+                Some(body)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                warn!("Failed to read disk cache for {}: {}", url, e);
+                None
+            }
+        }
+    }
+
+    /// Save a body to the disk cache; a failed write only costs a refetch, so it's just logged
+    fn store_on_disk(&self, url: &str, fetched: &FetchResponse, body: &str) {
+        let Some(disk_cache) = &self.disk_cache else {
+            return;
+        };
+        let now = self.clock.now_utc();
+        if let Err(e) = disk_cache.store(url, fetched.status, &fetched.headers, body, now) {
+            warn!("Failed to write disk cache for {}: {}", url, e);
+        }
+    }
+
+    /// Submit a form as `application/x-www-form-urlencoded` and return the response body
+    ///
+    /// POSTs are only retried when enabled with [`ScraperClientBuilder::retry_posts`], since they may not be idempotent.
+    /// Cancel-safe in the same way as [`ScraperClient::fetch_url`].
+    pub async fn post_form<U: Copy + IntoUrl>(
+        &self,
+        url: U,
+        params: &[(&str, &str)],
+    ) -> Result<String, ScraperError> {
+        self.post_form_with_retry(url, params, self.retry_posts)
+            .await
+    }
+
+    /// [`ScraperClient::post_form`], choosing for this call whether failed attempts are resent
+    pub async fn post_form_with_retry<U: Copy + IntoUrl>(
+        &self,
+        url: U,
+        params: &[(&str, &str)],
+        retry: bool,
+    ) -> Result<String, ScraperError> {
+        self.send_with_retries(
+            |client| client.post(url).form(params),
+            self.budget(self.post_retries(retry)),
+        )
+        .await
+        .map(|fetched| fetched.body)
+    }
+
+    /// Send `body` as JSON and return the response body
+    ///
+    /// Retried like [`ScraperClient::post_form`].
+    pub async fn post_json<U: Copy + IntoUrl, T: Serialize + ?Sized>(
+        &self,
+        url: U,
+        body: &T,
+    ) -> Result<String, ScraperError> {
+        self.post_json_with_retry(url, body, self.retry_posts).await
+    }
+
+    /// [`ScraperClient::post_json`], choosing for this call whether failed attempts are resent
+    pub async fn post_json_with_retry<U: Copy + IntoUrl, T: Serialize + ?Sized>(
+        &self,
+        url: U,
+        body: &T,
+        retry: bool,
+    ) -> Result<String, ScraperError> {
+        self.send_with_retries(
+            |client| client.post(url).json(body),
+            self.budget(self.post_retries(retry)),
+        )
+        .await
+        .map(|fetched| fetched.body)
+    }
+
+    /// `max_retries` bounded by the client's fetch deadline
+    fn budget(&self, max_retries: u8) -> RetryBudget {
+        RetryBudget {
+            max_retries,
+            deadline: self.fetch_deadline,
+        }
+    }
+
+    fn post_retries(&self, retry: bool) -> u8 {
+        if retry {
+            self.max_retries
+        } else {
+            0
+        }
+    }
+
+    /// Send the request built by `build_request`, retrying within `budget`
+    ///
+    /// Success and 304 Not Modified responses are returned with their body read.
+    async fn send_with_retries<F>(
+        &self,
+        build_request: F,
+        budget: RetryBudget,
+    ) -> Result<FetchResponse, ScraperError>
+    where
+        F: Fn(&Client) -> RequestBuilder,
+    {
+        self.send_reading(build_request, budget, ReadBody::Text)
+            .await
+            .map(|(response, _)| response)
+    }
+
+    /// [`ScraperClient::send_with_retries`], reading the body as `read` says
+    async fn send_reading<F>(
+        &self,
+        build_request: F,
+        budget: RetryBudget,
+        read: ReadBody,
+    ) -> Result<(FetchResponse, Vec<u8>), ScraperError>
+    where
+        F: Fn(&Client) -> RequestBuilder,
+    {
+        let start = self.clock.now_instant();
+        let request_id = self.request_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut progress = Progress::default();
+        let attempts = self.send_attempts(request_id, build_request, budget, read, &mut progress);
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "fetch",
+            request_id,
+            url = tracing::field::Empty,
+            attempts = tracing::field::Empty
+        );
+        #[cfg(feature = "tracing")]
+        let attempts = tracing::Instrument::instrument(attempts, span.clone());
+        let result = attempts.await;
+        #[cfg(feature = "tracing")]
+        span.record("attempts", progress.attempts);
+        self.record_result(&progress, result.is_ok(), self.clock.now_instant() - start);
+        if let (Ok((response, _)), ReadBody::Text) = (&result, read) {
+            self.write_snapshot(response);
+        }
+        result
+    }
+
+    /// Archive a successful response when [`ScraperClientBuilder::snapshot_dir`] is set
+    fn write_snapshot(&self, response: &FetchResponse) {
+        let Some(snapshots) = &self.snapshots else {
+            return;
+        };
+        if !(200..300).contains(&response.status) {
+            return;
+        }
+        match snapshots.write(response) {
+            Ok(path) => debug!(
+                "Saved snapshot of {} to {}",
+                response.final_url,
+                path.display()
+            ),
+            Err(e) => warn!("Failed to save snapshot of {}: {}", response.final_url, e),
+        }
+    }
+
+    /// The retry loop behind [`ScraperClient::send_with_retries`], which records the outcome in
+    /// the stats from `progress`
+    async fn send_attempts<F>(
+        &self,
+        request_id: u64,
+        build_request: F,
+        budget: RetryBudget,
+        read: ReadBody,
+        progress: &mut Progress,
+    ) -> Result<(FetchResponse, Vec<u8>), ScraperError>
+    where
+        F: Fn(&Client) -> RequestBuilder,
+    {
+        debug!("Request {}: starting", request_id);
+
+        let mut attempts = 0;
+        let start_time = self.clock.now_instant();
+
+        let RetryBudget {
+            max_retries,
+            deadline,
+        } = budget;
+
+        let mut last_error = None;
+
+        // Retry loop
+        while attempts <= max_retries {
+            attempts += 1;
+            progress.attempts = attempts;
+            let mut retry_after = None;
+            let mut request = match build_request(&self.client).build() {
+                Ok(request) => request,
+                Err(e) => {
+                    error!("Request {}: could not be built: {}", request_id, e);
+                    return Err(e.into());
+                }
+            };
+            self.run_request_hooks(request_id, attempts, &mut request);
+            progress.method = Some(request.method().clone());
+            #[cfg(feature = "tracing")]
+            if attempts == 1 {
+                tracing::Span::current().record("url", tracing::field::display(request.url()));
+            }
+            let host = request.url().host_str().unwrap_or_default().to_string();
+            progress.host = Some(host.clone());
+            let allowed = self
+                .circuit_breaker
+                .lock()
+                .unwrap()
+                .allow(&host, self.clock.now_instant());
+            if !allowed {
+                warn!("Request {}: circuit open for {}", request_id, host);
+                return Err(ScraperError::CircuitOpen(host));
+            }
+            if self.respect_robots_txt {
+                if let Err(e) = self.check_robots_txt(request.url()).await {
+                    warn!("Request {}: {}", request_id, e);
+                    return Err(e);
+                }
+            }
+            self.wait_for_rate_limit(request.url().host_str().unwrap_or_default())
+                .await;
+
+            // A User-Agent already on the request was set for this call and takes precedence
+            if !request.headers().contains_key(USER_AGENT) {
+                if let Some(user_agent) = self.user_agents.next_user_agent() {
+                    request.headers_mut().insert(USER_AGENT, user_agent);
+                }
+            }
+            debug!(
+                "Request {} attempt {}: {} {} with User-Agent {:?}",
+                request_id,
+                attempts,
+                request.method(),
+                request.url(),
+                request
+                    .headers()
+                    .get(USER_AGENT)
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or(&self.user_agent)
+            );
+
+            // Never let the attempt run past the deadline
+            let mut cut_short = false;
+            if let Some(deadline) = deadline {
+                let elapsed = self.clock.now_instant() - start_time;
+                if elapsed >= deadline {
+                    return Err(self.deadline_exceeded(request_id, elapsed, attempts - 1));
+                }
+                let remaining = deadline - elapsed;
+                if remaining < request.timeout().copied().unwrap_or(self.request_timeout) {
+                    *request.timeout_mut() = Some(remaining);
+                    cut_short = true;
+                }
+            }
+
+            let attempt_start = self.clock.now_instant();
+            let method = request.method().clone();
+            let url = request.url().clone();
+            let sent = self.execute_following_redirects(request_id, request).await;
+            let attempt_elapsed = self.clock.now_instant() - attempt_start;
+            progress.attempt_latencies.push(attempt_elapsed);
+            if let Ok((response, _)) = &sent {
+                self.run_response_hooks(&ResponseParts {
+                    request_id,
+                    attempt: attempts,
+                    method: &method,
+                    url: response.url(),
+                    status: response.status(),
+                    headers: response.headers(),
+                    elapsed: attempt_elapsed,
+                });
+            }
+            match sent.map_err(|e| tls::classify(self.timeout_error(e, attempt_start))) {
+                Ok((response, redirects)) => {
+                    self.record_clock_skew(response.headers().get(DATE));
+                    let status = response.status();
+                    if (self.retry_on_status)(status) {
+                        self.record_host_failure(&host);
+                    } else {
+                        self.circuit_breaker.lock().unwrap().record_success(&host);
+                    }
+                    if status.is_success() || status == StatusCode::NOT_MODIFIED {
+                        let headers = response.headers().clone();
+                        let final_url = response.url().clone();
+                        let read_result = match read {
+                            ReadBody::Text => body::read_body(response, self.max_body_bytes)
+                                .await
+                                .map(|(body, encoding, transfer)| {
+                                    (body, encoding.name(), Vec::new(), Some(transfer))
+                                }),
+                            ReadBody::Bytes => body::read_bytes(response, self.max_body_bytes)
+                                .await
+                                .map(|(raw, transfer)| {
+                                    (String::new(), "raw bytes", raw, Some(transfer))
+                                }),
+                            ReadBody::Skip => Ok((String::new(), "nothing", Vec::new(), None)),
+                        };
+                        let (body, encoding, raw, transfer) = match read_result {
+                            Ok(body) => body,
+                            Err(e) => match self.timeout_error(e, attempt_start) {
+                                ScraperError::Timeout { .. } if cut_short => {
+                                    let elapsed = self.clock.now_instant() - start_time;
+                                    return Err(
+                                        self.deadline_exceeded(request_id, elapsed, attempts)
+                                    );
+                                }
+                                e => return Err(e),
+                            },
+                        };
+                        if let Some(transfer) = &transfer {
+                            self.check_compression(request_id, &final_url, transfer);
+                        }
+                        progress.transfer.clone_from(&transfer);
+                        let elapsed = self.clock.now_instant() - start_time;
+                        #[cfg(feature = "tracing")]
+                        tracing::info!(
+                            attempt = attempts,
+                            status = status.as_u16(),
+                            elapsed_ms = elapsed.as_millis() as u64,
+                            "fetched"
+                        );
+                        info!(
+                            "Request {}: fetched {} on attempt {} after {:?} (decoded as {})",
+                            request_id, final_url, attempts, elapsed, encoding
+                        );
+                        if redirect::is_permanent(&redirects) {
+                            warn!(
+                                "Request {}: {} permanently redirects; content now lives at {}",
+                                request_id, redirects[0].url, final_url
+                            );
+                        }
+                        let response = FetchResponse {
+                            body,
+                            status: status.as_u16(),
+                            headers,
+                            final_url,
+                            redirects,
+                            encoding,
+                            attempts,
+                            elapsed,
+                            fetched_at: self.clock.now_utc(),
+                            transfer: transfer.unwrap_or_default(),
+                        };
+                        return Ok((response, raw));
+                    } else if !(self.retry_on_status)(response.status()) {
+                        #[cfg(feature = "tracing")]
+                        tracing::error!(
+                            attempt = attempts,
+                            status = status.as_u16(),
+                            elapsed_ms = (self.clock.now_instant() - start_time).as_millis() as u64,
+                            "failed"
+                        );
+                        error!(
+                            "Request {} attempt {}: failed with non-retryable status {}",
+                            request_id,
+                            attempts,
+                            response.status()
+                        );
+                        let error = self.status_error(response).await;
+                        self.notify_retry(request_id, &url, attempts, &error, None);
+                        return Err(error);
+                    } else {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!(
+                            attempt = attempts,
+                            status = status.as_u16(),
+                            "retryable status"
+                        );
+                        warn!(
+                            "Request {} attempt {}: failed with status {}",
+                            request_id,
+                            attempts,
+                            response.status()
+                        );
+                        retry_after = self.retry_after(&response);
+                        last_error = Some(self.status_error(response).await);
+                    }
+                }
+                Err(ScraperError::Timeout { .. }) if cut_short => {
+                    let elapsed = self.clock.now_instant() - start_time;
+                    return Err(self.deadline_exceeded(request_id, elapsed, attempts));
+                }
+                Err(e @ ScraperError::Timeout { .. }) => {
+                    warn!("Request {} attempt {}: {}", request_id, attempts, e);
+                    self.record_host_failure(&host);
+                    last_error = Some(e);
+                }
+                Err(ScraperError::FetchError(e)) if retry::is_retryable_error(&e) => {
+                    warn!("Request {} attempt {}: {}", request_id, attempts, e);
+                    self.record_host_failure(&host);
+                    last_error = Some(ScraperError::FetchError(e));
+                }
+                Err(e) => {
+                    error!(
+                        "Request {} attempt {}: could not be sent: {}",
+                        request_id, attempts, e
+                    );
+                    self.notify_retry(request_id, &url, attempts, &e, None);
+                    return Err(e);
+                }
+            }
+
+            let Some(error) = last_error.take() else {
+                continue;
+            };
+            if attempts <= max_retries {
+                let backoff = self
+                    .backoff
+                    .delay(u32::from(attempts), &mut rand::thread_rng());
+                let delay = retry_after.map_or(backoff, |retry_after| retry_after.max(backoff));
+                let elapsed = self.clock.now_instant() - start_time;
+                if deadline.is_some_and(|deadline| elapsed + delay >= deadline) {
+                    self.notify_retry(request_id, &url, attempts, &error, None);
+                    return Err(self.deadline_exceeded(request_id, elapsed, attempts));
+                }
+                if self.notify_retry(request_id, &url, attempts, &error, Some(delay)) {
+                    warn!(
+                        "Request {}: retries stopped by a callback after attempt {}",
+                        request_id, attempts
+                    );
+                    return Err(ScraperError::RetriesExhausted {
+                        attempts,
+                        last_error: Box::new(error),
+                    });
+                }
+                #[cfg(feature = "tracing")]
+                tracing::warn!(
+                    attempt = attempts,
+                    delay_ms = delay.as_millis() as u64,
+                    elapsed_ms = (self.clock.now_instant() - start_time).as_millis() as u64,
+                    "retrying"
+                );
+                debug!(
+                    "Request {}: retrying in {:?} (retry {})",
+                    request_id, delay, attempts
+                );
+                self.clock.sleep(delay).await;
+            } else {
+                self.notify_retry(request_id, &url, attempts, &error, None);
+            }
+            last_error = Some(error);
+        }
+
+        let elapsed = self.clock.now_instant() - start_time;
+        #[cfg(feature = "tracing")]
+        tracing::error!(
+            attempts,
+            elapsed_ms = elapsed.as_millis() as u64,
+            "retries exhausted"
+        );
+        error!(
+            "Request {}: giving up after {} attempts in {:?}",
+            request_id, attempts, elapsed
+        );
+        // Every attempt that reaches here failed in a retryable way and set `last_error`
+        let last_error = last_error.unwrap_or_else(|| {
+            ScraperError::CustomError(format!("Failed to fetch page in {:?}", elapsed))
+        });
+        Err(ScraperError::RetriesExhausted {
+            attempts,
+            last_error: Box::new(last_error),
+        })
+    }
+
+    /// Let the request hooks change `request`; the hooks lock isn't held while they run
+    fn run_request_hooks(&self, request_id: u64, attempt: u8, request: &mut Request) {
+        let hooks = self.hooks.lock().unwrap().request.clone();
+        if hooks.is_empty() {
+            return;
+        }
+        let mut parts = RequestParts {
+            request_id,
+            attempt,
+            method: request.method().clone(),
+            url: request.url().clone(),
+            headers: std::mem::take(request.headers_mut()),
+        };
+        for hook in &hooks {
+            hook(&mut parts);
+        }
+        *request.method_mut() = parts.method;
+        *request.url_mut() = parts.url;
+        *request.headers_mut() = parts.headers;
+    }
+
+    /// Warn about an uncompressed body when [`Compression::Required`] is set
+    fn check_compression(&self, request_id: u64, url: &Url, transfer: &Transfer) {
+        if self.compression == Compression::Required
+            && transfer.content_encoding.is_none()
+            && transfer.wire_bytes > 0
+        {
+            warn!(
+                "Request {}: {} sent {} bytes uncompressed despite Accept-Encoding: gzip",
+                request_id, url, transfer.wire_bytes
+            );
+        }
+    }
+
+    /// Tell the retry callbacks about a failed attempt, returning whether any asked to stop
+    fn notify_retry(
+        &self,
+        request_id: u64,
+        url: &Url,
+        attempt: u8,
+        error: &ScraperError,
+        delay: Option<Duration>,
+    ) -> bool {
+        let callbacks = self.hooks.lock().unwrap().retry.clone();
+        let event = RetryEvent {
+            request_id,
+            url,
+            attempt,
+            error,
+            delay,
+        };
+        let mut stop = false;
+        for callback in &callbacks {
+            stop |= callback(&event).is_break();
+        }
+        stop
+    }
+
+    fn run_response_hooks(&self, parts: &ResponseParts<'_>) {
+        let hooks = self.hooks.lock().unwrap().response.clone();
+        for hook in &hooks {
+            hook(parts);
+        }
+    }
+
+    /// `HttpStatus` for an unsuccessful response, with the start of its body for debugging
+    async fn status_error(&self, response: Response) -> ScraperError {
+        let status = response.status();
+        let url = response.url().to_string();
+        let body_snippet = match body::read_body(response, self.max_body_bytes).await {
+            Ok((body, _, _)) => body.trim().chars().take(BODY_SNIPPET_CHARS).collect(),
+            Err(_) => String::new(),
+        };
+        ScraperError::HttpStatus {
+            status,
+            url,
+            body_snippet,
+        }
+    }
+
+    fn deadline_exceeded(&self, request_id: u64, elapsed: Duration, attempts: u8) -> ScraperError {
+        error!(
+            "Request {}: deadline exceeded after {} attempts in {:?}",
+            request_id, attempts, elapsed
+        );
+        ScraperError::DeadlineExceeded { elapsed, attempts }
+    }
+
+    /// Turn a reqwest timeout from an attempt started at `attempt_start` into `ScraperError::Timeout`
+    fn timeout_error(&self, error: ScraperError, attempt_start: Instant) -> ScraperError {
+        match error {
+            ScraperError::FetchError(e) if e.is_timeout() => ScraperError::Timeout {
+                phase: if e.is_connect() {
+                    TimeoutPhase::Connect
+                } else {
+                    TimeoutPhase::Request
+                },
+                elapsed: self.clock.now_instant() - attempt_start,
+            },
+            other => other,
+        }
+    }
+
+    /// Send `request`, following redirects according to the redirect policy
+    ///
+    /// Returns the final response with the hops taken to reach it. Each hop waits for the rate
+    /// limit of its host. A chain longer than the limit, or one that revisits a URL, fails with
+    /// `TooManyRedirects`.
+    async fn execute_following_redirects(
+        &self,
+        request_id: u64,
+        mut request: Request,
+    ) -> Result<(Response, Vec<Redirect>), ScraperError> {
+        let mut redirects: Vec<Redirect> = Vec::new();
+        loop {
+            // Taken before sending, since `execute` consumes the request; streaming bodies can't be copied
+            let next_request = request.try_clone();
+            let response = self.client.execute(request).await?;
+            let status = response.status();
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|location| location.to_str().ok())
+                .and_then(|location| response.url().join(location).ok());
+            let (true, Some(location), RedirectPolicy::Limited(max_redirects), Some(next_request)) = (
+                status.is_redirection(),
+                location,
+                self.redirect_policy,
+                next_request,
+            ) else {
+                return Ok((response, redirects));
+            };
+
+            redirects.push(Redirect {
+                url: response.url().clone(),
+                status,
+            });
+            let revisits = redirects.iter().any(|redirect| redirect.url == location);
+            if revisits || redirects.len() > max_redirects {
+                return Err(ScraperError::TooManyRedirects { chain: redirects });
+            }
+            debug!(
+                "Request {}: redirected ({}) to {}",
+                request_id, status, location
+            );
+            self.wait_for_rate_limit(location.host_str().unwrap_or_default())
+                .await;
+            request = redirect::follow(next_request, status, location);
+        }
+    }
+
+    /// Fail with `DisallowedByRobots` when the host's robots.txt disallows `url` for our User-Agent
+    ///
+    /// robots.txt is fetched once per origin and cached for the lifetime of the client; concurrent
+    /// first requests to an origin wait for that single fetch.
+    async fn check_robots_txt(&self, url: &Url) -> Result<(), ScraperError> {
+        let origin = url.origin().ascii_serialization();
+        let cell = self
+            .robots_cache
+            .lock()
+            .unwrap()
+            .entry(origin.clone())
+            .or_default()
+            .clone();
+        let rules = cell
+            .get_or_init(|| async {
+                let host = url.host_str().unwrap_or_default();
+                let rules = self.fetch_robots_txt(&origin, host).await;
+                if let Some(crawl_delay) = rules.crawl_delay() {
+                    self.rate_limiter
+                        .lock()
+                        .unwrap()
+                        .set_host_interval(host, crawl_delay);
+                }
+                rules
+            })
+            .await;
+
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        if rules.is_allowed(&path) {
+            Ok(())
+        } else {
+            Err(ScraperError::DisallowedByRobots(url.to_string()))
+        }
+    }
+
+    /// Fetch and parse robots.txt; a missing or unreachable file allows everything
+    async fn fetch_robots_txt(&self, origin: &str, host: &str) -> RobotsRules {
+        self.wait_for_rate_limit(host).await;
+        let robots_url = format!("{}/robots.txt", origin);
+        let response = match self.client.get(&robots_url).send().await {
+            Ok(response) if response.status().is_success() => response,
+            Ok(response) => {
+                debug!("No robots.txt at {} ({})", robots_url, response.status());
+                return RobotsRules::default();
+            }
+            Err(e) => {
+                warn!("Failed to fetch {}: {}", robots_url, e);
+                return RobotsRules::default();
+            }
+        };
+        match body::read_body(response, self.max_body_bytes).await {
+            Ok((body, _, _)) => RobotsRules::parse(&body, &self.user_agent),
+            Err(e) => {
+                warn!("Failed to read {}: {}", robots_url, e);
+                RobotsRules::default()
+            }
+        }
+    }
+
+    /// Wait until the configured interval since the last request to `host` has passed
+    ///
+    /// This happens before the request is sent, so it doesn't count against the request timeout.
+    /// The slot is reserved before sleeping so concurrent requests to one host queue up in turn.
+    async fn wait_for_rate_limit(&self, host: &str) {
+        let delay = {
+            let mut rate_limiter = self.rate_limiter.lock().unwrap();
+            let now = self.clock.now_instant();
+            let delay = rate_limiter.delay_for(host, now);
+            rate_limiter.record(host, now + delay);
+            delay
+        };
+        if delay > Duration::ZERO {
+            debug!("Rate limiting {}: waiting {:?}", host, delay);
+            self.clock.sleep(delay).await;
+        }
+    }
+
+    /// Delay requested by a 429 or 503 response's `Retry-After` header, capped at `max_retry_after`
+    fn retry_after(&self, response: &reqwest::Response) -> Option<Duration> {
+        if !matches!(
+            response.status(),
+            StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+        ) {
+            return None;
+        }
+        let header = response.headers().get(RETRY_AFTER)?;
+        match parse_retry_after(header, self.clock.now_utc()) {
+            Some(delay) => Some(delay.min(self.max_retry_after)),
+            None => {
+                warn!("Ignoring unparseable Retry-After header: {:?}", header);
+                None
+            }
+        }
+    }
+
+    /// Remember the skew reported by a response and warn when it exceeds the threshold
+    fn record_clock_skew(&self, date_header: Option<&HeaderValue>) {
+        let Some(skew) = measure_clock_skew(date_header, self.clock.now_utc()) else {
+            return;
+        };
+        if skew.magnitude() > self.clock_skew_threshold {
+            warn!("Server clock differs from local clock: {:?}", skew);
+        }
+        *self.last_clock_skew.lock().unwrap() = Some(skew);
+    }
+
+    /// Count a retryable failure against `host`'s circuit breaker
+    fn record_host_failure(&self, host: &str) {
+        let opened = self
+            .circuit_breaker
+            .lock()
+            .unwrap()
+            .record_failure(host, self.clock.now_instant());
+        if opened {
+            warn!(
+                "Too many consecutive failures: opening circuit for {}",
+                host
+            );
+            self.stats_mut().totals.circuits_opened += 1;
+        }
+    }
+
+    /// Current circuit breaker state for `host`
+    pub fn circuit_state(&self, host: &str) -> CircuitState {
+        self.circuit_breaker
+            .lock()
+            .unwrap()
+            .state(host, self.clock.now_instant())
+    }
+
+    /// Track a finished request that took `elapsed` in the stats
+    fn record_result(&self, progress: &Progress, succeeded: bool, elapsed: Duration) {
+        let retries = u64::from(progress.attempts.saturating_sub(1));
+        let mut stats = self.stats_mut();
+        if let Some(host) = &progress.host {
+            stats.hosts.entry(host.clone()).or_default().record(
+                succeeded,
+                retries,
+                elapsed,
+                progress.transfer.as_ref(),
+            );
+        }
+        stats.request_latency.record(elapsed);
+        #[cfg(feature = "metrics")]
+        stats.duration_histogram.observe(elapsed);
+        for latency in &progress.attempt_latencies {
+            stats.attempt_latency.record(*latency);
+        }
+        let stats = &mut stats.totals;
+        stats.total_requests += 1;
+        if succeeded {
+            stats.successful_requests += 1;
+        } else {
+            stats.failed_requests += 1;
+        }
+        stats.retries += retries;
+        if let Some(transfer) = &progress.transfer {
+            stats.bytes_downloaded += transfer.wire_bytes;
+            stats.decoded_bytes += transfer.decoded_bytes;
+            let encoding = transfer.content_encoding.as_deref().unwrap_or("identity");
+            *stats
+                .content_encodings
+                .entry(encoding.to_string())
+                .or_default() += 1;
+        }
+        stats.elapsed += elapsed;
+        match progress.method.as_ref() {
+            Some(&Method::GET) => stats.get_requests += 1,
+            Some(&Method::POST) => stats.post_requests += 1,
+            _ => {}
+        }
+    }
+
+    /// A copy of the current stats; the lock is released before this returns
+    pub fn stats(&self) -> StatsSnapshot {
+        self.stats.lock().unwrap().snapshot()
+    }
+
+    /// Write the request metrics for [`crate::metrics::render_prometheus`]
+    #[cfg(feature = "metrics")]
+    pub(crate) fn write_metrics(&self, out: &mut crate::metrics::MetricsWriter) {
+        let stats = self.stats.lock().unwrap();
+        out.family(
+            "requests",
+            "counter",
+            "Finished fetches by host and outcome",
+        );
+        for (host, host_stats) in stats.by_host() {
+            out.sample(
+                "requests_total",
+                &[("host", &host), ("outcome", "success")],
+                host_stats.successes,
+            );
+            out.sample(
+                "requests_total",
+                &[("host", &host), ("outcome", "failure")],
+                host_stats.failures,
+            );
+        }
+        out.family(
+            "request_duration_seconds",
+            "histogram",
+            "Time from the first attempt of a fetch to its outcome",
+        );
+        stats
+            .duration_histogram
+            .write(out, "request_duration_seconds");
+        out.family(
+            "retries",
+            "counter",
+            "Attempts made after the first of each fetch",
+        );
+        out.sample("retries_total", &[], stats.totals.retries);
+        out.family(
+            "cancelled",
+            "counter",
+            "Fetches dropped because another mirror answered first",
+        );
+        out.sample("cancelled_total", &[], stats.totals.cancelled_requests);
+        out.family(
+            "bytes_downloaded",
+            "counter",
+            "Raw response body bytes read",
+        );
+        out.sample("bytes_downloaded_total", &[], stats.totals.bytes_downloaded);
+    }
+
+    /// Stats for each host requested so far, keyed and ordered by host name
+    ///
+    /// Hosts are never forgotten, so this grows with the number of distinct hosts until
+    /// [`ScraperClient::reset_stats`].
+    pub fn stats_by_host(&self) -> BTreeMap<String, HostStats> {
+        self.stats.lock().unwrap().by_host()
+    }
+
+    /// Zero every counter, e.g. between batches of a long-running job
+    pub fn reset_stats(&self) {
+        *self.stats_mut() = ScraperClientStats::default();
+    }
+
+    fn stats_mut(&self) -> MutexGuard<'_, ScraperClientStats> {
+        self.stats.lock().unwrap()
+    }
+
+    /// Print the current statistics (total requests, successes, failures) to stdout
+    pub fn print_stats(&self) {
+        for line in self.stats_lines() {
+            println!("{}", line);
+        }
+    }
+
+    /// Log the current statistics at info level, like [`ScraperClient::print_stats`]
+    pub fn log_stats(&self) {
+        for line in self.stats_lines() {
+            info!("{}", line);
+        }
+    }
+
+    fn stats_lines(&self) -> Vec<String> {
+        let stats = self.stats();
+        let mut lines = vec![format!(
+            "Total Requests: {} (GET: {}, POST: {}), Successful: {}, Failed: {}, Cache hits: {}, Cache misses: {}, Circuits opened: {}",
+            stats.total_requests,
+            stats.get_requests,
+            stats.post_requests,
+            stats.successful_requests,
+            stats.failed_requests,
+            stats.cache_hits,
+            stats.cache_misses,
+            stats.circuits_opened
+        )];
+        lines.push(format!(
+            "Retries: {}, Cancelled: {}, Bytes downloaded: {} ({} decompressed), Time spent: {:?}",
+            stats.retries,
+            stats.cancelled_requests,
+            stats.bytes_downloaded,
+            stats.decoded_bytes,
+            stats.elapsed
+        ));
+        if !stats.content_encodings.is_empty() {
+            let encodings: Vec<String> = stats
+                .content_encodings
+                .iter()
+                .map(|(encoding, count)| format!("{} {}", encoding, count))
+                .collect();
+            lines.push(format!("Content encodings: {}", encodings.join(", ")));
+        }
+        for (label, latency) in [
+            ("Request latency", stats.request_latency),
+            ("Attempt latency", stats.attempt_latency),
+        ] {
+            if let Some(latency) = latency {
+                lines.push(format!(
+                    "{}: min {:?}, mean {:?}, p50 {:?}, p95 {:?}, p99 {:?}, max {:?} ({} samples)",
+                    label,
+                    latency.min,
+                    latency.mean,
+                    latency.p50,
+                    latency.p95,
+                    latency.p99,
+                    latency.max,
+                    latency.samples
+                ));
+            }
+        }
+        let mut hosts: Vec<(String, HostStats)> = self.stats_by_host().into_iter().collect();
+        // Worst first; ties keep alphabetical order
+        hosts.sort_by(|(_, a), (_, b)| b.failure_rate().total_cmp(&a.failure_rate()));
+        if !hosts.is_empty() {
+            lines.push(format!(
+                "{:<30} {:>8} {:>8} {:>8} {:>8} {:>12}",
+                "Host", "Requests", "OK", "Failed", "Retries", "Mean latency"
+            ));
+        }
+        for (host, stats) in hosts {
+            lines.push(format!(
+                "{:<30} {:>8} {:>8} {:>8} {:>8} {:>12}",
+                host,
+                stats.requests,
+                stats.successes,
+                stats.failures,
+                stats.retries,
+                format!("{:?}", stats.mean_latency)
+            ));
+        }
+        let breaker = self.circuit_breaker.lock().unwrap();
+        let tripped = breaker.tripped_hosts();
+        if !tripped.is_empty() {
+            lines.push(format!("Open circuits: {}", tripped.join(", ")));
+        }
+        lines
+    }
+}
+
+/// Compare a server `Date` header with `now`; missing or unparseable headers give `None`
+pub fn measure_clock_skew(date_header: Option<&HeaderValue>, now: SystemTime) -> Option<ClockSkew> {
+    let server_time = httpdate::parse_http_date(date_header?.to_str().ok()?).ok()?;
+    // HTTP dates have one-second resolution, so compare at that precision
+    let now = httpdate::parse_http_date(&httpdate::fmt_http_date(now)).ok()?;
+    match server_time.duration_since(now) {
+        Ok(ahead) => Some(ClockSkew::ServerAhead(ahead)),
+        Err(behind) => Some(ClockSkew::ServerBehind(behind.duration())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use log::{Level, LevelFilter, Log, Metadata, Record};
+    use std::collections::BTreeMap;
+    use std::future::ready;
+    use std::thread::{self, ThreadId};
+    use wiremock::matchers::{body_string, header, header_exists, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// Collects log records with the thread that emitted them, since tests run in parallel
+    struct CaptureLogger(Mutex<Vec<(ThreadId, Level, String)>>);
+
+    static LOGGER: CaptureLogger = CaptureLogger(Mutex::new(Vec::new()));
+
+    impl Log for CaptureLogger {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            // reqwest and wiremock log on the same threads
+            metadata.target().starts_with(env!("CARGO_CRATE_NAME"))
+        }
+
+        fn log(&self, record: &Record) {
+            if !self.enabled(record.metadata()) {
+                return;
+            }
+            self.0.lock().unwrap().push((
+                thread::current().id(),
+                record.level(),
+                record.args().to_string(),
+            ));
+        }
+
+        fn flush(&self) {}
+    }
+
+    fn capture_logs() {
+        // Fails harmlessly when another test installed it first
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(LevelFilter::Debug);
+    }
+
+    /// Records logged so far by the current test's thread
+    fn captured_logs() -> Vec<(Level, String)> {
+        let current = thread::current().id();
+        LOGGER
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(thread, _, _)| *thread == current)
+            .map(|(_, level, message)| (*level, message.clone()))
+            .collect()
+    }
+
+    fn test_client() -> ScraperClient {
+        test_builder().build().unwrap()
+    }
+
+    fn test_builder() -> ScraperClientBuilder {
+        ScraperClient::builder()
+            .request_timeout(Duration::from_secs(5))
+            .max_retries(2)
+            .retry_delay(Duration::from_millis(10))
+    }
+
+    #[tokio::test]
+    async fn test_post_form_encodes_body() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/holidays"))
+            .and(header("content-type", "application/x-www-form-urlencoded"))
+            .and(body_string("year=2025&region=WA+%26+regional"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("<table></table>", "text/html"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client();
+        let body = client
+            .post_form(
+                format!("{}/holidays", server.uri()).as_str(),
+                &[("year", "2025"), ("region", "WA & regional")],
+            )
+            .await
+            .expect("POST failed");
+
+        assert_eq!(body, "<table></table>");
+        assert_eq!(client.stats().successful_requests, 1);
+    }
+
+    #[tokio::test]
+    async fn test_post_form_not_retried_by_default() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client();
+        let result = client
+            .post_form(server.uri().as_str(), &[("year", "2025")])
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(client.stats().failed_requests, 1);
+    }
+
+    #[tokio::test]
+    async fn test_post_form_retried_when_enabled() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(4)
+            .mount(&server)
+            .await;
+
+        let clock = Arc::new(MockClock::default());
+        let client = ScraperClient::builder()
+            .retry_posts(true)
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+        let result = client
+            .post_form(server.uri().as_str(), &[("year", "2025")])
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(clock.sleeps(), vec![Duration::from_secs(2); 3]);
+    }
+
+    #[tokio::test]
+    async fn test_post_form_with_retry_recovers_after_500() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_string("year=2025"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("<table></table>", "text/html"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_builder()
+            .clock(Arc::new(MockClock::default()))
+            .build()
+            .unwrap();
+        let body = client
+            .post_form_with_retry(server.uri().as_str(), &[("year", "2025")], true)
+            .await
+            .unwrap();
+
+        assert_eq!(body, "<table></table>");
+        assert_eq!(client.stats().successful_requests, 1);
+    }
+
+    #[tokio::test]
+    async fn test_post_json_encodes_body_and_counts_methods() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/search"))
+            .and(header("content-type", "application/json"))
+            .and(body_string(r#"{"region":"WA","year":"2025"}"#))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("<table></table>", "text/html"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).insert_header("content-type", "text/html"))
+            .mount(&server)
+            .await;
+
+        let client = test_client();
+        let query = BTreeMap::from([("year", "2025"), ("region", "WA")]);
+        let body = client
+            .post_json(format!("{}/search", server.uri()).as_str(), &query)
+            .await
+            .unwrap();
+        client.fetch_url(server.uri().as_str()).await.unwrap();
+
+        assert_eq!(body, "<table></table>");
+        let stats = client.stats();
+        assert_eq!((stats.get_requests, stats.post_requests), (1, 1));
+        assert_eq!(stats.total_requests, 2);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_cancelled_before_first_poll() {
+        let client = test_client();
+
+        tokio::select! {
+            biased;
+            _ = ready(()) => {}
+            _ = client.fetch_url("http://127.0.0.1:9/unreachable") => panic!("Fetch should not win"),
+        }
+
+        assert_eq!(client.stats().total_requests, 0);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_cancelled_mid_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/html")
+                    .set_delay(Duration::from_secs(5)),
+            )
+            .mount(&server)
+            .await;
+
+        let client = test_client();
+        let result = tokio::time::timeout(
+            Duration::from_millis(100),
+            client.fetch_url(server.uri().as_str()),
+        )
+        .await;
+
+        assert!(result.is_err(), "Fetch should have been cancelled");
+        assert_eq!(client.stats().total_requests, 0);
+        let stats = client.stats();
+        assert_eq!(
+            stats.total_requests,
+            stats.successful_requests + stats.failed_requests
+        );
+
+        // The client stays usable after a cancelled fetch
+        server.reset().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("ok", "text/html"))
+            .mount(&server)
+            .await;
+        let body = client.fetch_url(server.uri().as_str()).await.unwrap();
+        assert_eq!(body, "ok");
+        assert_eq!(client.stats().total_requests, 1);
+        assert_eq!(client.stats().successful_requests, 1);
+    }
+
+    #[test]
+    fn test_measure_clock_skew() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let header =
+            |time: SystemTime| HeaderValue::from_str(&httpdate::fmt_http_date(time)).unwrap();
+
+        assert_eq!(
+            measure_clock_skew(Some(&header(now + Duration::from_secs(90))), now),
+            Some(ClockSkew::ServerAhead(Duration::from_secs(90)))
+        );
+        assert_eq!(
+            measure_clock_skew(Some(&header(now - Duration::from_secs(30))), now),
+            Some(ClockSkew::ServerBehind(Duration::from_secs(30)))
+        );
+        assert_eq!(
+            measure_clock_skew(Some(&header(now)), now),
+            Some(ClockSkew::ServerAhead(Duration::ZERO))
+        );
+        assert_eq!(measure_clock_skew(None, now), None);
+        assert_eq!(
+            measure_clock_skew(Some(&HeaderValue::from_static("yesterday")), now),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_records_clock_skew() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/html")
+                    .insert_header("date", "Tue, 14 Nov 2023 22:15:20 GMT"),
+            )
+            .mount(&server)
+            .await;
+
+        // The mock clock is two minutes ahead of the server's Date header
+        let now = httpdate::parse_http_date("Tue, 14 Nov 2023 22:17:20 GMT").unwrap();
+        let client = test_builder()
+            .clock(Arc::new(MockClock::new(now)))
+            .build()
+            .unwrap();
+        client.fetch_url(server.uri().as_str()).await.unwrap();
+
+        assert_eq!(
+            client.last_clock_skew(),
+            Some(ClockSkew::ServerBehind(Duration::from_secs(120)))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_exponential_backoff_delays() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(4)
+            .mount(&server)
+            .await;
+
+        let clock = Arc::new(MockClock::default());
+        let client = ScraperClient::builder()
+            .backoff(BackoffPolicy::Exponential {
+                base: Duration::from_secs(1),
+                max: Duration::from_secs(3),
+                jitter: false,
+            })
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+
+        assert!(client.fetch_url(server.uri().as_str()).await.is_err());
+        assert_eq!(
+            clock.sleeps(),
+            vec![
+                Duration::from_secs(1),
+                Duration::from_secs(2),
+                Duration::from_secs(3)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_honours_retry_after() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "30"))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503).insert_header("retry-after", "3600"))
+            .up_to_n_times(1)
+            .with_priority(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503).insert_header("retry-after", "whenever"))
+            .up_to_n_times(1)
+            .with_priority(3)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("ok", "text/html"))
+            .with_priority(4)
+            .mount(&server)
+            .await;
+
+        let clock = Arc::new(MockClock::default());
+        let client = ScraperClient::builder()
+            .retry_delay(Duration::from_secs(2))
+            .max_retry_after(Duration::from_secs(300))
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+
+        let body = client.fetch_url(server.uri().as_str()).await.unwrap();
+        assert_eq!(body, "ok");
+        assert_eq!(
+            clock.sleeps(),
+            vec![
+                Duration::from_secs(30),
+                Duration::from_secs(300),
+                Duration::from_secs(2)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_does_not_retry_404() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client();
+        let url = format!("{}/missing", server.uri());
+        let result = client.fetch_url(url.as_str()).await;
+
+        match result {
+            Err(ScraperError::HttpStatus {
+                status,
+                url: failed_url,
+                ..
+            }) => {
+                assert_eq!(status, StatusCode::NOT_FOUND);
+                assert_eq!(failed_url, url);
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        assert_eq!(client.stats().failed_requests, 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_retry_predicate_override() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        // Some servers briefly 404 while a page is being republished
+        let client = test_builder()
+            .retry_on_status(|status| status == StatusCode::NOT_FOUND || status.is_server_error())
+            .build()
+            .unwrap();
+        let result = client.fetch_url(server.uri().as_str()).await;
+
+        match result {
+            Err(ScraperError::RetriesExhausted {
+                attempts,
+                last_error,
+            }) => {
+                assert_eq!(attempts, 3);
+                assert_eq!(last_error.status(), Some(StatusCode::NOT_FOUND));
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_exhausted_retries_keep_last_status() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503).set_body_string("  Down for maintenance  "))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = test_builder().max_retries(1).build().unwrap();
+        let result = client.fetch_url(server.uri().as_str()).await;
+
+        let Err(ScraperError::RetriesExhausted {
+            attempts,
+            last_error,
+        }) = result
+        else {
+            panic!("expected retries to run out, got {:?}", result);
+        };
+        assert_eq!(attempts, 2);
+        match *last_error {
+            ScraperError::HttpStatus {
+                status,
+                body_snippet,
+                ..
+            } => {
+                assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+                assert_eq!(body_snippet, "Down for maintenance");
+            }
+            other => panic!("expected an HTTP status, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_forbidden_keeps_body_snippet() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(403).set_body_string("x".repeat(500)))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_builder().build().unwrap();
+        let err = client.fetch_url(server.uri().as_str()).await.unwrap_err();
+
+        assert_eq!(err.status(), Some(StatusCode::FORBIDDEN));
+        let ScraperError::HttpStatus { body_snippet, .. } = err else {
+            panic!("expected an HTTP status, got {:?}", err);
+        };
+        assert_eq!(body_snippet.len(), BODY_SNIPPET_CHARS);
+    }
+
+    #[tokio::test]
+    async fn test_poll_until_changed_returns_both_bodies() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("v1", "text/html"))
+            .up_to_n_times(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("v2", "text/html"))
+            .mount(&server)
+            .await;
+
+        let clock = Arc::new(MockClock::new(SystemTime::now()));
+        let client = test_builder().clock(clock.clone()).build().unwrap();
+        let change = client
+            .poll_until_changed(server.uri().as_str(), Duration::from_secs(60), 5)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            change,
+            Some(ContentChange {
+                old_body: "v1".to_string(),
+                new_body: "v2".to_string(),
+                polls: 3,
+            })
+        );
+        assert_eq!(clock.sleeps(), vec![Duration::from_secs(60); 2]);
+    }
+
+    #[tokio::test]
+    async fn test_poll_until_changed_ignores_normalised_parts() {
+        let server = MockServer::start().await;
+        for body in ["Generated 1\nTable A", "Generated 2\nTable A"] {
+            Mock::given(method("GET"))
+                .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/html"))
+                .up_to_n_times(1)
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw("Generated 3\nTable A", "text/html"),
+            )
+            .mount(&server)
+            .await;
+
+        let clock = Arc::new(MockClock::new(SystemTime::now()));
+        let client = test_builder().clock(clock.clone()).build().unwrap();
+        let strip_first_line = |body: &str| body.lines().skip(1).collect::<Vec<_>>().join("\n");
+        let change = client
+            .poll_until_changed_with(
+                server.uri().as_str(),
+                Duration::from_secs(1),
+                4,
+                strip_first_line,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(change, None);
+        assert_eq!(clock.sleeps(), vec![Duration::from_secs(1); 3]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_rejects_pdf_and_json() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/holidays.pdf"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw("%PDF-1.7\n%\u{e2}\u{e3}", "application/pdf"),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/holidays.json"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw("{\"holidays\": []}", "application/json"),
+            )
+            .mount(&server)
+            .await;
+
+        let client = test_builder().build().unwrap();
+        for (name, content_type) in [("pdf", "application/pdf"), ("json", "application/json")] {
+            let url = format!("{}/holidays.{}", server.uri(), name);
+            match client.fetch_url(url.as_str()).await {
+                Err(ScraperError::UnexpectedContentType {
+                    expected,
+                    got,
+                    url: failed_url,
+                }) => {
+                    assert_eq!(expected, "text/html, application/xhtml+xml");
+                    assert_eq!(got, content_type);
+                    assert_eq!(failed_url, url);
+                }
+                other => panic!("Unexpected result: {:?}", other),
+            }
+        }
+
+        let url = format!("{}/holidays.json", server.uri());
+        let body = client.fetch_url_unchecked(url.as_str()).await.unwrap();
+        assert_eq!(body, "{\"holidays\": []}");
+        let json_client = test_builder()
+            .accept_content_types(["application/json"])
+            .build()
+            .unwrap();
+        assert!(json_client.fetch_url(url.as_str()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_head_returns_headers_without_reading_body() {
+        let server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/holidays.pdf"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("last-modified", "Wed, 01 Jan 2025 00:00:00 GMT")
+                    .set_body_raw(vec![0; 2048], "application/pdf"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // A body over the limit would fail a GET, but HEAD responses have none
+        let client = test_builder().max_body_bytes(1024).build().unwrap();
+        let url = format!("{}/holidays.pdf", server.uri());
+        let head = client.head(url.as_str()).await.unwrap();
+
+        assert_eq!(head.status, 200);
+        assert_eq!(head.final_url.as_str(), url);
+        assert_eq!(head.content_length(), Some(2048));
+        assert_eq!(
+            head.last_modified(),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_735_689_600))
+        );
+        let stats = client.stats();
+        assert_eq!(stats.successful_requests, 1);
+        assert_eq!(stats.bytes_downloaded, 0);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_bytes_round_trips_binary_body() {
+        let body: Vec<u8> = (0..=255).collect();
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/holidays.ics"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body.clone(), "text/calendar"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/large.pdf"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(vec![0; 2048], "application/pdf"))
+            .mount(&server)
+            .await;
+
+        let client = test_builder().max_body_bytes(1024).build().unwrap();
+        let fetched = client
+            .fetch_bytes(format!("{}/holidays.ics", server.uri()).as_str())
+            .await
+            .unwrap();
+        assert_eq!(fetched, body);
+        assert_eq!(client.stats().bytes_downloaded, 256);
+
+        let result = client
+            .fetch_bytes(format!("{}/large.pdf", server.uri()).as_str())
+            .await;
+        assert!(matches!(
+            result,
+            Err(ScraperError::ResponseTooLarge { limit: 1024, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_tls_failure_is_not_retried() {
+        // Speaking TLS to a plain HTTP server fails the handshake
+        let server = MockServer::start().await;
+        let url = server.uri().replace("http://", "https://");
+
+        let client = test_builder().build().unwrap();
+        let result = client.fetch_url(url.as_str()).await;
+
+        assert!(matches!(result, Err(ScraperError::Tls(_))), "{:?}", result);
+        assert_eq!(client.stats().retries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_request_hook_injects_header_on_every_attempt() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("x-signature", "signed"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(header("x-signature", "signed"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("holidays", "text/html"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_builder().build().unwrap();
+        client.add_request_hook(|request| {
+            request
+                .headers
+                .insert("x-signature", HeaderValue::from_static("signed"));
+        });
+        let attempts = Arc::new(Mutex::new(Vec::new()));
+        let seen = attempts.clone();
+        client.add_response_hook(move |response| {
+            seen.lock()
+                .unwrap()
+                .push((response.attempt, response.url.clone(), response.status));
+        });
+        let body = client.fetch_url(server.uri().as_str()).await.unwrap();
+
+        assert_eq!(body, "holidays");
+        let url = Url::parse(&server.uri()).unwrap();
+        assert_eq!(
+            *attempts.lock().unwrap(),
+            vec![
+                (1, url.clone(), StatusCode::INTERNAL_SERVER_ERROR),
+                (2, url, StatusCode::OK),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_hook_rewrites_url_to_mirror() {
+        let mirror = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/archive/holidays"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("archived", "text/html"))
+            .expect(1)
+            .mount(&mirror)
+            .await;
+
+        let client = test_builder().build().unwrap();
+        let mirror_url = Url::parse(&mirror.uri()).unwrap();
+        let requested = Arc::new(Mutex::new(Vec::new()));
+        let seen = requested.clone();
+        client.add_request_hook(move |request| {
+            seen.lock().unwrap().push(request.url.to_string());
+            let path = format!("/archive{}", request.url.path());
+            request.url = mirror_url.join(&path).unwrap();
+        });
+        let body = client
+            .fetch_url("http://127.0.0.1:9/holidays")
+            .await
+            .unwrap();
+
+        assert_eq!(body, "archived");
+        assert_eq!(
+            *requested.lock().unwrap(),
+            vec!["http://127.0.0.1:9/holidays".to_string()]
+        );
+    }
+
+    type RetryRecord = (u8, Option<StatusCode>, Option<Duration>);
+
+    /// Records `(attempt, status, delay)` for every retry event
+    fn collect_retries(client: &ScraperClient) -> Arc<Mutex<Vec<RetryRecord>>> {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let seen = events.clone();
+        client.on_retry(move |event| {
+            seen.lock()
+                .unwrap()
+                .push((event.attempt, event.error.status(), event.delay));
+            ControlFlow::Continue(())
+        });
+        events
+    }
+
+    #[tokio::test]
+    async fn test_on_retry_reports_each_failed_attempt() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("holidays", "text/html"))
+            .mount(&server)
+            .await;
+
+        let client = test_builder().build().unwrap();
+        let events = collect_retries(&client);
+        client.fetch_url(server.uri().as_str()).await.unwrap();
+
+        let delay = Some(Duration::from_millis(10));
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                (1, Some(StatusCode::SERVICE_UNAVAILABLE), delay),
+                (2, Some(StatusCode::INTERNAL_SERVER_ERROR), delay),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_on_retry_reports_final_attempt_without_delay() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = test_builder().max_retries(1).build().unwrap();
+        let events = collect_retries(&client);
+        let result = client.fetch_url(server.uri().as_str()).await;
+
+        assert!(matches!(
+            result,
+            Err(ScraperError::RetriesExhausted { attempts: 2, .. })
+        ));
+        let status = Some(StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                (1, status, Some(Duration::from_millis(10))),
+                (2, status, None)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_on_retry_break_stops_retrying() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_builder().max_retries(5).build().unwrap();
+        client.on_retry(|_| ControlFlow::Break(()));
+        let result = client.fetch_url(server.uri().as_str()).await;
+
+        match result {
+            Err(ScraperError::RetriesExhausted {
+                attempts,
+                last_error,
+            }) => {
+                assert_eq!(attempts, 1);
+                assert_eq!(last_error.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    fn gzip(body: &[u8]) -> Vec<u8> {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(body).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_fetch_decompresses_gzip_and_records_transfer() {
+        let page = "<table>".repeat(200);
+        let compressed = gzip(page.as_bytes());
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-encoding", "gzip")
+                    .set_body_raw(compressed.clone(), "text/html"),
+            )
+            .mount(&server)
+            .await;
+
+        let client = test_builder().build().unwrap();
+        let response = client.fetch(server.uri().as_str()).await.unwrap();
+
+        assert_eq!(response.body, page);
+        assert_eq!(
+            response.transfer,
+            Transfer {
+                wire_bytes: compressed.len() as u64,
+                decoded_bytes: page.len() as u64,
+                content_encoding: Some("gzip".to_string()),
+            }
+        );
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests[0].headers["accept-encoding"], "gzip, deflate");
+        let stats = client.stats();
+        assert_eq!(stats.bytes_downloaded, compressed.len() as u64);
+        assert_eq!(stats.decoded_bytes, page.len() as u64);
+        assert_eq!(
+            stats.content_encodings,
+            BTreeMap::from([("gzip".to_string(), 1)])
+        );
+        let host = &client.stats_by_host()["127.0.0.1"];
+        assert_eq!(host.bytes_downloaded, compressed.len() as u64);
+        assert_eq!(host.decoded_bytes, page.len() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_with_compression_disabled_asks_for_identity() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("accept-encoding", "identity"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("holidays", "text/html"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_builder()
+            .compression(Compression::Disabled)
+            .build()
+            .unwrap();
+        let response = client.fetch(server.uri().as_str()).await.unwrap();
+
+        assert_eq!(response.transfer.content_encoding, None);
+        assert_eq!(
+            client.stats().content_encodings,
+            BTreeMap::from([("identity".to_string(), 1)])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_limits_decompressed_size() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-encoding", "gzip")
+                    .set_body_raw(gzip(&[b'<'; 100_000]), "text/html"),
+            )
+            .mount(&server)
+            .await;
+
+        let client = test_builder().max_body_bytes(10_000).build().unwrap();
+        let result = client.fetch(server.uri().as_str()).await;
+
+        assert!(matches!(
+            result,
+            Err(ScraperError::ResponseTooLarge { limit: 10_000, .. })
+        ));
+    }
+
+    async fn mirror(status: u16, body: &str, delay: Duration) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(status)
+                    .set_body_raw(body, "text/html")
+                    .set_delay(delay),
+            )
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn test_fetch_first_of_takes_fastest_mirror() {
+        let slow = mirror(200, "slow", Duration::from_secs(5)).await;
+        let fast = mirror(200, "fast", Duration::from_millis(20)).await;
+
+        let client = test_client();
+        let started = Instant::now();
+        let (body, url) = client
+            .fetch_first_of(&[slow.uri(), fast.uri()])
+            .await
+            .unwrap();
+
+        assert_eq!(body, "fast");
+        assert_eq!(url, fast.uri());
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(slow.received_requests().await.unwrap().len(), 1);
+        let stats = client.stats();
+        assert_eq!(stats.successful_requests, 1);
+        assert_eq!(stats.failed_requests, 0);
+        assert_eq!(stats.cancelled_requests, 1);
+        assert_eq!(stats.total_requests, 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_first_of_skips_failed_mirror() {
+        let broken = mirror(404, "gone", Duration::ZERO).await;
+        let working = mirror(200, "holidays", Duration::from_millis(50)).await;
+
+        let client = test_client();
+        let (body, url) = client
+            .fetch_first_of(&[broken.uri(), working.uri()])
+            .await
+            .unwrap();
+
+        assert_eq!((body.as_str(), url), ("holidays", working.uri()));
+        let stats = client.stats();
+        assert_eq!((stats.successful_requests, stats.failed_requests), (1, 1));
+        assert_eq!(stats.cancelled_requests, 0);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_first_of_reports_every_failure() {
+        let first = mirror(404, "", Duration::ZERO).await;
+        let second = mirror(403, "", Duration::from_millis(20)).await;
+
+        let client = test_client();
+        let err = client
+            .fetch_first_of(&[first.uri(), second.uri()])
+            .await
+            .unwrap_err();
+
+        match err {
+            ScraperError::PartialFailure { succeeded, errors } => {
+                assert_eq!(succeeded, 0);
+                let urls: Vec<&str> = errors.iter().map(|(url, _)| url.as_str()).collect();
+                assert_eq!(urls, vec![first.uri(), second.uri()]);
+                assert_eq!(errors[1].1.status(), Some(StatusCode::FORBIDDEN));
+            }
+            other => panic!("Unexpected error: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_first_of_hedge_delay_spares_later_mirrors() {
+        let primary = mirror(200, "primary", Duration::ZERO).await;
+        let backup = mirror(200, "backup", Duration::ZERO).await;
+
+        let client = test_builder()
+            .hedge_delay(Duration::from_secs(10))
+            .build()
+            .unwrap();
+        let (body, _) = client
+            .fetch_first_of(&[primary.uri(), backup.uri()])
+            .await
+            .unwrap();
+
+        assert_eq!(body, "primary");
+        assert!(backup.received_requests().await.unwrap().is_empty());
+        // The backup never sent anything, so there was nothing to cancel
+        assert_eq!(client.stats().cancelled_requests, 0);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_first_of_without_urls() {
+        let urls: [&str; 0] = [];
+        let result = test_client().fetch_first_of(&urls).await;
+        assert!(matches!(result, Err(ScraperError::CustomError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_rate_limited_per_host() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).insert_header("content-type", "text/html"))
+            .mount(&server)
+            .await;
+
+        let clock = Arc::new(MockClock::default());
+        let client = test_builder()
+            .min_request_interval(Duration::from_secs(5))
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+
+        let ip_url = server.uri();
+        let localhost_url = ip_url.replace("127.0.0.1", "localhost");
+        client.fetch_url(ip_url.as_str()).await.unwrap();
+        client.fetch_url(localhost_url.as_str()).await.unwrap();
+        client.fetch_url(ip_url.as_str()).await.unwrap();
+
+        // Only the second request to 127.0.0.1 waited
+        assert_eq!(clock.sleeps(), vec![Duration::from_secs(5)]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_request_timeout() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/html")
+                    .set_delay(Duration::from_secs(2)),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = test_builder()
+            .max_retries(1)
+            .request_timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+        let result = client.fetch_url(server.uri().as_str()).await;
+
+        let Err(ScraperError::RetriesExhausted { last_error, .. }) = result else {
+            panic!("expected retries to run out, got {:?}", result);
+        };
+        match *last_error {
+            ScraperError::Timeout { phase, elapsed } => {
+                assert_eq!(phase, TimeoutPhase::Request);
+                assert!(elapsed >= Duration::from_millis(100));
+            }
+            other => panic!("expected a timeout, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_with_timeout_overrides_client_timeout() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw("slow", "text/html")
+                    .set_delay(Duration::from_millis(300)),
+            )
+            .mount(&server)
+            .await;
+
+        let client = test_builder()
+            .max_retries(0)
+            .request_timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+        let url = server.uri();
+
+        let body = client
+            .fetch_url_with_timeout(url.as_str(), Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(body, "slow");
+        let result = client
+            .fetch_url_with_timeout(url.as_str(), Duration::from_millis(50))
+            .await;
+        assert!(matches!(
+            result,
+            Err(ScraperError::RetriesExhausted { last_error, .. })
+                if matches!(*last_error, ScraperError::Timeout { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_with_headers_overrides_defaults_once() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/override"))
+            .and(header("accept-language", "en-AU"))
+            .and(header("referer", "https://example.com/"))
+            .and(header("x-source", "call"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("override", "text/html"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/plain"))
+            .and(header("x-source", "default"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("plain", "text/html"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_builder()
+            .default_header("X-Source", "default")
+            .build()
+            .unwrap();
+        let body = client
+            .fetch_url_with_headers(
+                format!("{}/override", server.uri()).as_str(),
+                &[
+                    ("Accept-Language", "en-AU"),
+                    ("Referer", "https://example.com/"),
+                    ("X-Source", "call"),
+                ],
+            )
+            .await
+            .unwrap();
+        assert_eq!(body, "override");
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests[0].headers.get_all("x-source").iter().count(), 1);
+
+        // The override is not left behind on the client
+        let plain_url = format!("{}/plain", server.uri());
+        assert_eq!(client.fetch_url(plain_url.as_str()).await.unwrap(), "plain");
+        let requests = server.received_requests().await.unwrap();
+        assert!(requests[1].headers.get("accept-language").is_none());
+        assert!(requests[1].headers.get("referer").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_with_headers_rejects_invalid_value() {
+        let client = test_client();
+        let result = client
+            .fetch_url_with_headers("http://127.0.0.1:1/", &[("Referer", "a\r\nb")])
+            .await;
+
+        assert!(matches!(result, Err(ScraperError::InvalidHeader(_))));
+        assert_eq!(client.stats().total_requests, 0);
+    }
+
+    #[tokio::test]
+    async fn test_stats_count_retries_bytes_and_time() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/flaky"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("hello", "text/html"))
+            .mount(&server)
+            .await;
+
+        let clock = Arc::new(MockClock::default());
+        let client = test_builder().clock(clock.clone()).build().unwrap();
+        client
+            .fetch_url(format!("{}/flaky", server.uri()).as_str())
+            .await
+            .unwrap();
+        client.fetch_url(server.uri().as_str()).await.unwrap();
+
+        let stats = client.stats();
+        assert_eq!(stats.total_requests, 2);
+        assert_eq!(stats.successful_requests, 2);
+        assert_eq!(stats.failed_requests, 0);
+        assert_eq!(stats.retries, 1);
+        assert_eq!(stats.bytes_downloaded, 10);
+        assert_eq!(stats.elapsed, clock.elapsed());
+        assert_eq!(stats.request_latency.unwrap().samples, 2);
+        assert_eq!(stats.request_latency.unwrap().max, clock.elapsed());
+        assert_eq!(stats.attempt_latency.unwrap().samples, 3);
+
+        client.reset_stats();
+        assert_eq!(client.stats(), StatsSnapshot::default());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    async fn test_fetch_url_traces_span_and_events() {
+        use tracing_subscriber::fmt::format::FmtSpan;
+
+        #[derive(Clone, Default)]
+        struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+        impl std::io::Write for Buffer {
+            fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().write(bytes)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).insert_header("content-type", "text/html"))
+            .mount(&server)
+            .await;
+
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+        let client = test_builder()
+            .clock(Arc::new(MockClock::new(SystemTime::now())))
+            .build()
+            .unwrap();
+        client.fetch_url(server.uri().as_str()).await.unwrap();
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let span = format!("fetch{{request_id=1 url={}/}}", server.uri());
+        assert!(output.contains(&format!(
+            "{}: rust_assignment::scraper_client: retryable status attempt=1 status=503",
+            span
+        )));
+        assert!(output.contains("retrying attempt=1 delay_ms=10"));
+        assert!(output.contains("fetched attempt=2 status=200 elapsed_ms="));
+        // The span closes with the number of attempts recorded
+        assert!(output.contains(&format!(
+            "fetch{{request_id=1 url={}/ attempts=2}}",
+            server.uri()
+        )));
+        assert!(output.contains("close time.busy="));
+    }
+
+    #[tokio::test]
+    async fn test_stats_by_host_attributes_each_host() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/up"))
+            .respond_with(ResponseTemplate::new(200).insert_header("content-type", "text/html"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/down"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let client = test_builder()
+            .max_retries(1)
+            .clock(Arc::new(MockClock::default()))
+            .build()
+            .unwrap();
+        // The same server reached through two host names
+        let up_url = format!("{}/up", server.uri());
+        let down_url = format!("{}/down", server.uri()).replace("127.0.0.1", "localhost");
+        client.fetch_url(up_url.as_str()).await.unwrap();
+        client.fetch_url(up_url.as_str()).await.unwrap();
+        assert!(client.fetch_url(down_url.as_str()).await.is_err());
+
+        let by_host = client.stats_by_host();
+        assert_eq!(by_host.len(), 2);
+        let up = &by_host["127.0.0.1"];
+        assert_eq!(
+            (up.requests, up.successes, up.failures, up.retries),
+            (2, 2, 0, 0)
+        );
+        let down = &by_host["localhost"];
+        assert_eq!(
+            (down.requests, down.successes, down.failures, down.retries),
+            (1, 0, 1, 1)
+        );
+        assert_eq!(down.failure_rate(), 1.0);
+
+        let lines = client.stats_lines();
+        let table_start = lines
+            .iter()
+            .position(|line| line.starts_with("Host"))
+            .unwrap();
+        assert!(lines[table_start + 1].starts_with("localhost"));
+        assert!(lines[table_start + 2].starts_with("127.0.0.1"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_logs_at_expected_levels() {
+        capture_logs();
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/flaky"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/flaky"))
+            .respond_with(ResponseTemplate::new(200).insert_header("content-type", "text/html"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/down"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+
+        let client = test_builder()
+            // Real start time, so the mock server's Date header doesn't trigger a clock skew warning
+            .clock(Arc::new(MockClock::new(SystemTime::now())))
+            .build()
+            .unwrap();
+        client
+            .fetch_url(format!("{}/flaky", server.uri()).as_str())
+            .await
+            .unwrap();
+        let flaky_logs = captured_logs();
+        let levels: Vec<Level> = flaky_logs.iter().map(|(level, _)| *level).collect();
+        assert_eq!(
+            levels.iter().filter(|level| **level == Level::Warn).count(),
+            1
+        );
+        assert_eq!(
+            levels.iter().filter(|level| **level == Level::Info).count(),
+            1
+        );
+        assert!(levels.contains(&Level::Debug));
+        assert!(!levels.contains(&Level::Error));
+        assert!(flaky_logs.iter().all(
+            |(_, message)| !message.starts_with("Request") || message.starts_with("Request 1")
+        ));
+
+        let result = client
+            .fetch_url(format!("{}/down", server.uri()).as_str())
+            .await;
+        assert!(result.is_err());
+        let down_logs = &captured_logs()[flaky_logs.len()..];
+        assert!(down_logs
+            .iter()
+            .any(|(level, message)| *level == Level::Error
+                && message.starts_with("Request 2: giving up")));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_deadline_cuts_slow_attempt_short() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/html")
+                    .set_delay(Duration::from_secs(2)),
+            )
+            .mount(&server)
+            .await;
+
+        let client = test_builder()
+            .max_retries(3)
+            .fetch_deadline(Duration::from_millis(300))
+            .build()
+            .unwrap();
+        let started = Instant::now();
+        let result = client.fetch_url(server.uri().as_str()).await;
+
+        // Within the budget plus some slack, rather than a full 5s request timeout
+        assert!(started.elapsed() < Duration::from_secs(1));
+        match result {
+            Err(ScraperError::DeadlineExceeded { elapsed, attempts }) => {
+                assert!(elapsed >= Duration::from_millis(300));
+                assert_eq!(attempts, 1);
+            }
+            other => panic!("expected the deadline to be exceeded, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_with_deadline_stops_retrying() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let clock = Arc::new(MockClock::default());
+        let client = test_builder()
+            .max_retries(5)
+            .retry_delay(Duration::from_secs(2))
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+        let result = client
+            .fetch_url_with_deadline(server.uri().as_str(), Duration::from_secs(3))
+            .await;
+
+        // A second 2s delay would end past the deadline, so there is no third attempt
+        assert!(matches!(
+            result,
+            Err(ScraperError::DeadlineExceeded { attempts: 2, elapsed }) if elapsed == Duration::from_secs(2)
+        ));
+        assert_eq!(clock.sleeps(), vec![Duration::from_secs(2)]);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_opens_and_recovers() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("back", "text/html"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let clock = Arc::new(MockClock::default());
+        let client = test_builder()
+            .max_retries(0)
+            .circuit_breaker(2, Duration::from_secs(30))
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+        let url = server.uri();
+
+        assert!(client.fetch_url(url.as_str()).await.is_err());
+        assert!(client.fetch_url(url.as_str()).await.is_err());
+        assert_eq!(client.circuit_state("127.0.0.1"), CircuitState::Open);
+
+        // The open circuit fails fast without reaching the server
+        let result = client.fetch_url(url.as_str()).await;
+        assert!(matches!(result, Err(ScraperError::CircuitOpen(host)) if host == "127.0.0.1"));
+
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(client.circuit_state("127.0.0.1"), CircuitState::HalfOpen);
+        assert_eq!(client.fetch_url(url.as_str()).await.unwrap(), "back");
+        assert_eq!(client.circuit_state("127.0.0.1"), CircuitState::Closed);
+        assert_eq!(client.stats().circuits_opened, 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_rate_limit_spacing() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).insert_header("content-type", "text/html"))
+            .mount(&server)
+            .await;
+
+        let clock = Arc::new(MockClock::default());
+        let client = test_builder()
+            .requests_per_second(5.0)
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+
+        client.fetch_url(server.uri().as_str()).await.unwrap();
+        client.fetch_url(server.uri().as_str()).await.unwrap();
+        assert_eq!(clock.sleeps(), vec![Duration::from_millis(200)]);
+    }
+
+    async fn robots_server() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/robots.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                "User-agent: *\nDisallow: /private\nAllow: /private/holidays\nCrawl-delay: 7\n",
+                "text/html",
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("ok", "text/html"))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_robots_allow_and_disallow() {
+        let server = robots_server().await;
+        let client = test_builder()
+            .respect_robots_txt(true)
+            .clock(Arc::new(MockClock::default()))
+            .build()
+            .unwrap();
+
+        let allowed = format!("{}/private/holidays", server.uri());
+        assert_eq!(client.fetch_url(allowed.as_str()).await.unwrap(), "ok");
+
+        let disallowed = format!("{}/private/staff", server.uri());
+        match client.fetch_url(disallowed.as_str()).await {
+            Err(ScraperError::DisallowedByRobots(url)) => assert_eq!(url, disallowed),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        assert_eq!(request_count(&server, "/private/staff").await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_robots_crawl_delay() {
+        let server = robots_server().await;
+        let clock = Arc::new(MockClock::default());
+        let client = test_builder()
+            .respect_robots_txt(true)
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+
+        client.fetch_url(server.uri().as_str()).await.unwrap();
+        client.fetch_url(server.uri().as_str()).await.unwrap();
+
+        // Both page fetches wait out the crawl delay: one after robots.txt, one after the first page
+        assert_eq!(clock.sleeps(), vec![Duration::from_secs(7); 2]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_ignores_robots_by_default() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("ok", "text/html"))
+            .mount(&server)
+            .await;
+
+        let client = test_client();
+        client.fetch_url(server.uri().as_str()).await.unwrap();
+        assert_eq!(request_count(&server, "/robots.txt").await, 0);
+    }
+
+    async fn request_count(server: &MockServer, route: &str) -> usize {
+        server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .iter()
+            .filter(|request| request.url.path() == route)
+            .count()
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_rotates_user_agents() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).insert_header("content-type", "text/html"))
+            .mount(&server)
+            .await;
+
+        let client = test_builder()
+            .user_agents(
+                &["agent-one/1.0", "agent-two/2.0"],
+                UserAgentRotation::RoundRobin,
+            )
+            .build()
+            .unwrap();
+        for _ in 0..3 {
+            client.fetch_url(server.uri().as_str()).await.unwrap();
+        }
+
+        let agents: Vec<String> = server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .map(|request| request.headers["user-agent"].to_str().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            agents,
+            vec!["agent-one/1.0", "agent-two/2.0", "agent-one/1.0"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_custom_user_agent() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("user-agent", "holiday-bot/2.0"))
+            .respond_with(ResponseTemplate::new(200).insert_header("content-type", "text/html"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_builder()
+            .user_agent("holiday-bot/2.0")
+            .build()
+            .unwrap();
+        client.fetch_url(server.uri().as_str()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_through_http_proxy() {
+        // The mock server stands in for the proxy: requests arrive with the target in absolute form
+        let proxy = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/holidays"))
+            .and(header("proxy-authorization", "Basic dXNlcjpzZWNyZXQ="))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("via proxy", "text/html"))
+            .expect(1)
+            .mount(&proxy)
+            .await;
+
+        let proxy_url = proxy.uri().replace("http://", "http://user:secret@");
+        let client = test_builder().http_proxy(&proxy_url).build().unwrap();
+        let body = client
+            .fetch_url("http://holidays.example.invalid/holidays")
+            .await
+            .unwrap();
+        assert_eq!(body, "via proxy");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_cached_not_modified() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("if-none-match", "\"v1\""))
+            .and(header_exists("if-modified-since"))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("etag", "\"v1\"")
+                    .insert_header("last-modified", "Wed, 01 Jan 2025 00:00:00 GMT")
+                    .set_body_raw("holidays", "text/html"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client();
+        let url = format!("{}/holidays", server.uri());
+        assert_eq!(
+            client.fetch_url_cached(url.as_str()).await.unwrap(),
+            FetchOutcome::Fresh("holidays".into())
+        );
+        assert_eq!(
+            client.fetch_url_cached(url.as_str()).await.unwrap(),
+            FetchOutcome::NotModified("holidays".into())
+        );
+        assert_eq!(client.stats().cache_hits, 1);
+        assert_eq!(client.stats().successful_requests, 2);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_cached_changed_etag_replaces_entry() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("if-none-match", "\"v2\""))
+            .respond_with(ResponseTemplate::new(304))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(header("if-none-match", "\"v1\""))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("etag", "\"v2\"")
+                    .set_body_raw("updated", "text/html"),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("etag", "\"v1\"")
+                    .set_body_raw("original", "text/html"),
+            )
+            .mount(&server)
+            .await;
+
+        let client = test_client();
+        let url = format!("{}/holidays", server.uri());
+        assert_eq!(client.fetch_url(url.as_str()).await.unwrap(), "original");
+        assert_eq!(
+            client.fetch_url_cached(url.as_str()).await.unwrap(),
+            FetchOutcome::Fresh("updated".into())
+        );
+        assert_eq!(
+            client.fetch_url_cached(url.as_str()).await.unwrap(),
+            FetchOutcome::NotModified("updated".into())
+        );
+        assert_eq!(client.stats().cache_hits, 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_disk_cache_ttl() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("holidays", "text/html"))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let clock = Arc::new(MockClock::default());
+        let client = test_builder()
+            .clock(clock.clone())
+            .disk_cache(dir.path().join("pages"), Duration::from_secs(60))
+            .build()
+            .unwrap();
+        let url = format!("{}/holidays", server.uri());
+
+        assert_eq!(
+            client.fetch_url_cached(url.as_str()).await.unwrap(),
+            FetchOutcome::Fresh("holidays".into())
+        );
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(
+            client.fetch_url_cached(url.as_str()).await.unwrap(),
+            FetchOutcome::Cached("holidays".into())
+        );
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(
+            client.fetch_url_cached(url.as_str()).await.unwrap(),
+            FetchOutcome::Fresh("holidays".into())
+        );
+
+        assert_eq!(client.stats().total_requests, 2);
+        assert_eq!(client.stats().cache_hits, 1);
+        assert_eq!(client.stats().cache_misses, 2);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_writes_snapshots_with_retention() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/holidays"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("holidays", "text/html"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/missing"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let clock = Arc::new(MockClock::new(SystemTime::now()));
+        let client = test_builder()
+            .clock(clock.clone())
+            .snapshot_dir(dir.path())
+            .snapshot_retention(1)
+            .build()
+            .unwrap();
+        let url = format!("{}/holidays", server.uri());
+        client.fetch_url(url.as_str()).await.unwrap();
+        clock.advance(Duration::from_secs(1));
+        client.fetch_url(url.as_str()).await.unwrap();
+        let missing = format!("{}/missing", server.uri());
+        client.fetch_url(missing.as_str()).await.unwrap_err();
+
+        let host_dir = std::fs::read_dir(dir.path())
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        let mut names: Vec<_> = std::fs::read_dir(host_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(names.len(), 2, "{:?}", names);
+        assert!(names[0].starts_with("holidays-") && names[0].ends_with(".html"));
+        assert!(names[1].starts_with("holidays-") && names[1].ends_with(".json"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_disk_cache_cancelled_records_no_miss() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/html")
+                    .set_delay(Duration::from_secs(5)),
+            )
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let client = test_builder()
+            .disk_cache(dir.path(), Duration::from_secs(60))
+            .build()
+            .unwrap();
+        let result = tokio::time::timeout(
+            Duration::from_millis(100),
+            client.fetch_url(server.uri().as_str()),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(client.stats().cache_misses, 0);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_disk_cache_records_revalidation_status() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/html")
+                    .insert_header("etag", "\"v1\""),
+            )
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let clock = Arc::new(MockClock::default());
+        let client = test_builder()
+            .clock(clock.clone())
+            .disk_cache(dir.path(), Duration::from_secs(60))
+            .build()
+            .unwrap();
+        client.fetch_url(server.uri().as_str()).await.unwrap();
+        clock.advance(Duration::from_secs(60));
+        client.fetch_url(server.uri().as_str()).await.unwrap();
+
+        let meta_path = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "meta")
+            })
+            .unwrap();
+        let meta = std::fs::read_to_string(meta_path).unwrap();
+        assert!(meta.contains("status: 304\n"), "{}", meta);
+    }
+
+    #[tokio::test]
+    async fn test_cache_clear_forces_refetch() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("holidays", "text/html"))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let client = test_builder()
+            .disk_cache(dir.path(), Duration::from_secs(3600))
+            .build()
+            .unwrap();
+        let url = format!("{}/holidays", server.uri());
+
+        client.fetch_url(url.as_str()).await.unwrap();
+        client.cache_clear().unwrap();
+        client.fetch_url(url.as_str()).await.unwrap();
+        assert_eq!(client.stats().cache_hits, 0);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_urls_keeps_going_after_failures() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/missing"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("holidays", "text/html"))
+            .mount(&server)
+            .await;
+
+        let client = test_client();
+        let urls = [
+            format!("{}/wa", server.uri()),
+            format!("{}/missing", server.uri()),
+            format!("{}/nsw", server.uri()),
+        ];
+        let results = client.fetch_urls(&urls, 2).await;
+
+        let fetched: Vec<&str> = results.iter().map(|(url, _)| url.as_str()).collect();
+        assert_eq!(fetched, urls.iter().map(String::as_str).collect::<Vec<_>>());
+        assert_eq!(results[0].1.as_deref().unwrap(), "holidays");
+        assert!(matches!(
+            results[1].1,
+            Err(ScraperError::HttpStatus {
+                status: StatusCode::NOT_FOUND,
+                ..
+            })
+        ));
+        assert_eq!(results[2].1.as_deref().unwrap(), "holidays");
+        assert_eq!(client.stats().total_requests, 3);
+        assert_eq!(client.stats().failed_requests, 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_urls_runs_concurrently() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw("holidays", "text/html")
+                    .set_delay(Duration::from_millis(300)),
+            )
+            .expect(4)
+            .mount(&server)
+            .await;
+
+        let client = test_client();
+        let urls: Vec<String> = ["wa", "nsw", "vic", "qld"]
+            .iter()
+            .map(|state| format!("{}/{}", server.uri(), state))
+            .collect();
+        let start = std::time::Instant::now();
+        let results = client.fetch_urls(&urls, 4).await;
+
+        assert!(results.iter().all(|(_, result)| result.is_ok()));
+        assert!(start.elapsed() < Duration::from_millis(1000));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_urls_shares_rate_limit() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).insert_header("content-type", "text/html"))
+            .mount(&server)
+            .await;
+
+        let clock = Arc::new(MockClock::default());
+        let client = test_builder()
+            .clock(clock.clone())
+            .min_request_interval(Duration::from_secs(1))
+            .build()
+            .unwrap();
+        let urls = [
+            format!("{}/wa", server.uri()),
+            format!("{}/nsw", server.uri()),
+            format!("{}/vic", server.uri()),
+        ];
+        client.fetch_urls(&urls, 3).await;
+
+        // Each request reserves the next slot, so they start at 0s, 1s and 2s on the shared clock
+        assert_eq!(
+            clock.sleeps(),
+            vec![Duration::from_secs(1), Duration::from_secs(1)]
+        );
+        assert_eq!(clock.elapsed(), Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_urls_fetches_robots_once_per_origin() {
+        let server = robots_server().await;
+        let client = test_builder()
+            .respect_robots_txt(true)
+            .clock(Arc::new(MockClock::default()))
+            .build()
+            .unwrap();
+        let urls: Vec<Url> = ["/a", "/b", "/c", "/d"]
+            .iter()
+            .map(|route| Url::parse(&format!("{}{}", server.uri(), route)).unwrap())
+            .collect();
+
+        let results = client.fetch_urls(&urls, 4).await;
+
+        assert!(results.iter().all(|(_, result)| result.is_ok()));
+        assert_eq!(request_count(&server, "/robots.txt").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_rejects_large_content_length() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("x".repeat(2048), "text/html"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_builder().max_body_bytes(1024).build().unwrap();
+        let result = client.fetch_url(server.uri().as_str()).await;
+
+        assert!(matches!(
+            result,
+            Err(ScraperError::ResponseTooLarge { limit: 1024, .. })
+        ));
+        assert_eq!(client.stats().failed_requests, 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_stops_reading_endless_body() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // A chunked response without Content-Length that never ends; buffering it all would hang
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0; 1024];
+            let _ = socket.read(&mut request).await;
+            let head = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n";
+            if socket.write_all(head.as_bytes()).await.is_err() {
+                return;
+            }
+            let chunk = format!("400\r\n{}\r\n", "x".repeat(1024));
+            while socket.write_all(chunk.as_bytes()).await.is_ok() {}
+        });
+
+        let client = test_builder().max_body_bytes(64 * 1024).build().unwrap();
+        let result = client
+            .fetch_url(format!("http://{}/", address).as_str())
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(ScraperError::ResponseTooLarge { limit: 65536, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_body_at_limit() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("x".repeat(1024), "text/html"))
+            .mount(&server)
+            .await;
+
+        let client = test_builder().max_body_bytes(1024).build().unwrap();
+        let body = client.fetch_url(server.uri().as_str()).await.unwrap();
+        assert_eq!(body.len(), 1024);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_decodes_latin1_without_charset_header() {
+        let server = MockServer::start().await;
+        let mut page = b"<table><tr><td>F\xeate de l'Arm\xe9e</td></tr></table>".to_vec();
+        page.extend_from_slice(b"<p>Se\xf1or</p>");
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(page, "text/html"))
+            .mount(&server)
+            .await;
+
+        let client = test_client();
+        let body = client.fetch_url(server.uri().as_str()).await.unwrap();
+        assert_eq!(
+            body,
+            "<table><tr><td>Fête de l'Armée</td></tr></table><p>Señor</p>"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_keeps_session_cookie() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/start"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/html")
+                    .insert_header("set-cookie", "session=abc123; Path=/"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/holidays"))
+            .and(header("cookie", "session=abc123"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("holidays", "text/html"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_builder().with_cookies(true).build().unwrap();
+        client
+            .fetch_url(format!("{}/start", server.uri()).as_str())
+            .await
+            .unwrap();
+        let body = client
+            .fetch_url(format!("{}/holidays", server.uri()).as_str())
+            .await
+            .unwrap();
+
+        assert_eq!(body, "holidays");
+        assert_eq!(
+            client.cookies(&server.uri()).unwrap(),
+            vec![("session".to_string(), "abc123".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_cookie_seeds_the_jar() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("cookie", "consent=yes"))
+            .respond_with(ResponseTemplate::new(200).insert_header("content-type", "text/html"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_builder().with_cookies(true).build().unwrap();
+        client.add_cookie(&server.uri(), "consent", "yes").unwrap();
+        client.fetch_url(server.uri().as_str()).await.unwrap();
+
+        assert!(test_client()
+            .add_cookie(&server.uri(), "consent", "yes")
+            .is_err());
+    }
+
+    async fn redirect_server() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/old"))
+            .respond_with(ResponseTemplate::new(301).insert_header("location", "/new"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/new"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("holidays", "text/html"))
+            .mount(&server)
+            .await;
+        for (from, to) in [("/loop-a", "/loop-b"), ("/loop-b", "/loop-a")] {
+            Mock::given(method("GET"))
+                .and(path(from))
+                .respond_with(ResponseTemplate::new(302).insert_header("location", to))
+                .mount(&server)
+                .await;
+        }
+        server
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_follows_redirect_chain() {
+        let server = redirect_server().await;
+        let client = test_client();
+        let old_url = format!("{}/old", server.uri());
+
+        let fetched = client.fetch(old_url.as_str()).await.unwrap();
+
+        assert_eq!(fetched.body, "holidays");
+        assert_eq!(fetched.final_url.path(), "/new");
+        assert_eq!(
+            fetched.redirects,
+            vec![Redirect {
+                url: Url::parse(&old_url).unwrap(),
+                status: StatusCode::MOVED_PERMANENTLY,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_redirect_loop() {
+        let server = redirect_server().await;
+        let client = test_client();
+
+        let result = client
+            .fetch_url(format!("{}/loop-a", server.uri()).as_str())
+            .await;
+
+        let Err(ScraperError::TooManyRedirects { chain }) = result else {
+            panic!("expected TooManyRedirects, got {:?}", result);
+        };
+        let paths: Vec<&str> = chain.iter().map(|redirect| redirect.url.path()).collect();
+        assert_eq!(paths, vec!["/loop-a", "/loop-b"]);
+        assert_eq!(request_count(&server, "/loop-a").await, 1);
+        assert_eq!(client.stats().failed_requests, 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url_redirect_limit_and_disabled() {
+        let server = redirect_server().await;
+        let url = format!("{}/old", server.uri());
+
+        let limited = test_builder()
+            .redirect_policy(RedirectPolicy::Limited(0))
+            .build()
+            .unwrap();
+        let result = limited.fetch_url(url.as_str()).await;
+        let Err(error @ ScraperError::TooManyRedirects { .. }) = result else {
+            panic!("expected TooManyRedirects, got {:?}", result);
+        };
+        assert!(error.to_string().contains("/old (301)"), "{}", error);
+
+        let disabled = test_builder()
+            .redirect_policy(RedirectPolicy::None)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            disabled.fetch_url(url.as_str()).await,
+            Err(ScraperError::HttpStatus {
+                status: StatusCode::MOVED_PERMANENTLY,
+                ..
+            })
+        ));
+        assert_eq!(request_count(&server, "/new").await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_returns_metadata() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("x-source", "fixture")
+                    .set_body_raw("holidays", "text/html; charset=utf-8"),
+            )
+            .with_priority(2)
+            .mount(&server)
+            .await;
+
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let clock = Arc::new(MockClock::new(start));
+        let client = test_builder().clock(clock).build().unwrap();
+        let url = format!("{}/holidays", server.uri());
+        let response = client.fetch(url.as_str()).await.unwrap();
+
+        assert_eq!(response.body, "holidays");
+        assert_eq!(response.status, 200);
+        assert_eq!(response.headers["x-source"], "fixture");
+        assert_eq!(response.content_type(), Some("text/html; charset=utf-8"));
+        assert_eq!(response.final_url.as_str(), url);
+        assert!(response.redirects.is_empty());
+        assert_eq!(response.encoding, "UTF-8");
+        assert_eq!(response.attempts, 2);
+        assert_eq!(response.elapsed, Duration::from_millis(10));
+        assert_eq!(response.fetched_at, start + Duration::from_millis(10));
+    }
+}
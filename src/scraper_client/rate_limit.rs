@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Spaces out consecutive requests to the same host
+///
+/// Keeps one timestamp per distinct host the client has contacted.
+#[derive(Debug, Default)]
+pub(crate) struct RateLimiter {
+    min_interval: Duration,
+    host_intervals: HashMap<String, Duration>,
+    last_request: HashMap<String, Instant>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(min_interval: Duration) -> Self {
+        Self {
+            min_interval,
+            ..Self::default()
+        }
+    }
+
+    /// Require at least `interval` between requests to `host`, e.g. from a robots.txt Crawl-delay
+    pub(crate) fn set_host_interval(&mut self, host: &str, interval: Duration) {
+        self.host_intervals.insert(host.to_string(), interval);
+    }
+
+    /// How long a request to `host` made at `now` has to wait
+    pub(crate) fn delay_for(&self, host: &str, now: Instant) -> Duration {
+        match self.last_request.get(host) {
+            Some(last) => (*last + self.interval_for(host)).saturating_duration_since(now),
+            None => Duration::ZERO,
+        }
+    }
+
+    /// Remember that a request to `host` was sent at `now`
+    pub(crate) fn record(&mut self, host: &str, now: Instant) {
+        self.last_request.insert(host.to_string(), now);
+    }
+
+    fn interval_for(&self, host: &str) -> Duration {
+        self.host_intervals
+            .get(host)
+            .map_or(self.min_interval, |interval| {
+                (*interval).max(self.min_interval)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter_delays_per_host() {
+        let mut limiter = RateLimiter::new(Duration::from_secs(1));
+        let start = Instant::now();
+
+        assert_eq!(limiter.delay_for("a.example", start), Duration::ZERO);
+        limiter.record("a.example", start);
+
+        let later = start + Duration::from_millis(300);
+        assert_eq!(
+            limiter.delay_for("a.example", later),
+            Duration::from_millis(700)
+        );
+        assert_eq!(limiter.delay_for("b.example", later), Duration::ZERO);
+        assert_eq!(
+            limiter.delay_for("a.example", start + Duration::from_secs(2)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_rate_limiter_disabled() {
+        let mut limiter = RateLimiter::default();
+        let now = Instant::now();
+        limiter.record("a.example", now);
+        assert_eq!(limiter.delay_for("a.example", now), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_rate_limiter_host_interval() {
+        let mut limiter = RateLimiter::new(Duration::from_secs(1));
+        limiter.set_host_interval("slow.example", Duration::from_secs(10));
+        let now = Instant::now();
+
+        limiter.record("slow.example", now);
+        limiter.record("fast.example", now);
+        assert_eq!(
+            limiter.delay_for("slow.example", now),
+            Duration::from_secs(10)
+        );
+        assert_eq!(
+            limiter.delay_for("fast.example", now),
+            Duration::from_secs(1)
+        );
+    }
+}
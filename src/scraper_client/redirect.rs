@@ -0,0 +1,116 @@
+use reqwest::header::{
+    HeaderMap, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, COOKIE, PROXY_AUTHORIZATION,
+};
+use reqwest::{Method, Request, StatusCode, Url};
+use std::fmt;
+
+/// How the client follows redirect responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedirectPolicy {
+    /// Follow up to this many redirects per request
+    Limited(usize),
+    /// Return redirect responses as they are, which fails the fetch with `HttpStatus`
+    None,
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        RedirectPolicy::Limited(10)
+    }
+}
+
+/// One hop in a redirect chain: the URL that answered with a redirect and its status
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redirect {
+    pub url: Url,
+    pub status: StatusCode,
+}
+
+impl fmt::Display for Redirect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.url, self.status.as_u16())
+    }
+}
+
+/// Whether any hop was a permanent redirect, meaning the caller should update its URL
+pub(crate) fn is_permanent(chain: &[Redirect]) -> bool {
+    chain.iter().any(|redirect| {
+        matches!(
+            redirect.status,
+            StatusCode::MOVED_PERMANENTLY | StatusCode::PERMANENT_REDIRECT
+        )
+    })
+}
+
+/// Turn a copy of the original request into the request for the redirect target
+///
+/// 301, 302 and 303 switch non-GET/HEAD requests to a bodiless GET, as browsers do; 307 and 308
+/// keep the method and body. Credentials are dropped when the redirect leaves the origin.
+pub(crate) fn follow(mut request: Request, status: StatusCode, location: Url) -> Request {
+    let changes_method = matches!(
+        status,
+        StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND | StatusCode::SEE_OTHER
+    ) && !matches!(*request.method(), Method::GET | Method::HEAD);
+    if changes_method {
+        *request.method_mut() = Method::GET;
+        *request.body_mut() = None;
+        remove(request.headers_mut(), &[CONTENT_TYPE, CONTENT_LENGTH]);
+    }
+    if request.url().origin() != location.origin() {
+        remove(
+            request.headers_mut(),
+            &[AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION],
+        );
+    }
+    *request.url_mut() = location;
+    request
+}
+
+fn remove(headers: &mut HeaderMap, names: &[reqwest::header::HeaderName]) {
+    for name in names {
+        headers.remove(name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn post(url: &str) -> Request {
+        let mut request = Request::new(Method::POST, Url::parse(url).unwrap());
+        *request.body_mut() = Some("year=2025".into());
+        let headers = request.headers_mut();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer token"));
+        request
+    }
+
+    #[test]
+    fn test_follow_see_other_switches_to_get() {
+        let location = Url::parse("https://example.com/result").unwrap();
+        let request = follow(
+            post("https://example.com/form"),
+            StatusCode::SEE_OTHER,
+            location,
+        );
+        assert_eq!(request.method(), Method::GET);
+        assert!(request.body().is_none());
+        assert!(!request.headers().contains_key(CONTENT_TYPE));
+        assert!(request.headers().contains_key(AUTHORIZATION));
+    }
+
+    #[test]
+    fn test_follow_temporary_redirect_keeps_method_but_not_credentials_across_origins() {
+        let location = Url::parse("https://mirror.example.net/form").unwrap();
+        let request = follow(
+            post("https://example.com/form"),
+            StatusCode::TEMPORARY_REDIRECT,
+            location.clone(),
+        );
+        assert_eq!(request.method(), Method::POST);
+        assert!(request.body().is_some());
+        assert!(!request.headers().contains_key(AUTHORIZATION));
+        assert_eq!(request.url(), &location);
+    }
+}
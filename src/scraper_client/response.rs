@@ -0,0 +1,76 @@
+use super::redirect::{self, Redirect};
+use reqwest::header::{HeaderMap, CONTENT_LENGTH, CONTENT_TYPE, LAST_MODIFIED};
+use reqwest::Url;
+use std::time::{Duration, SystemTime};
+
+/// A fetched page together with what the client learned while fetching it
+#[derive(Debug, Clone)]
+pub struct FetchResponse {
+    /// The body decoded to UTF-8
+    pub body: String,
+    pub status: u16,
+    pub headers: HeaderMap,
+    /// The URL the body came from, after any redirects
+    pub final_url: Url,
+    /// Redirect hops taken to reach `final_url`, oldest first
+    pub redirects: Vec<Redirect>,
+    /// Name of the encoding the body was decoded from, e.g. "UTF-8" or "windows-1252"
+    pub encoding: &'static str,
+    /// Number of attempts, including the one that succeeded
+    pub attempts: u8,
+    /// Time from the first attempt until the body was read, including retry delays
+    pub elapsed: Duration,
+    /// When the body was read, according to the client's clock
+    pub fetched_at: SystemTime,
+    /// How large the body was on the wire and once decompressed
+    pub transfer: Transfer,
+}
+
+/// How a response body was transferred
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Transfer {
+    /// Bytes received, before decompression
+    pub wire_bytes: u64,
+    /// Bytes after decompression, before decoding to text
+    pub decoded_bytes: u64,
+    /// The `Content-Encoding` the body was sent with in lower case, or `None` if uncompressed
+    pub content_encoding: Option<String>,
+}
+
+impl FetchResponse {
+    /// The `Content-Type` header, if present and readable
+    pub fn content_type(&self) -> Option<&str> {
+        self.headers.get(CONTENT_TYPE)?.to_str().ok()
+    }
+
+    /// Whether a permanent redirect was followed, so the requested URL should be updated
+    pub fn moved_permanently(&self) -> bool {
+        redirect::is_permanent(&self.redirects)
+    }
+}
+
+/// The status and headers returned for a HEAD request
+#[derive(Debug, Clone)]
+pub struct HeadResponse {
+    pub status: u16,
+    pub headers: HeaderMap,
+    /// The URL that answered, after any redirects
+    pub final_url: Url,
+}
+
+impl HeadResponse {
+    /// The `Content-Length` header, if present and valid
+    pub fn content_length(&self) -> Option<u64> {
+        self.headers
+            .get(CONTENT_LENGTH)?
+            .to_str()
+            .ok()?
+            .parse()
+            .ok()
+    }
+
+    /// The `Last-Modified` header, if present and a valid HTTP date
+    pub fn last_modified(&self) -> Option<SystemTime> {
+        httpdate::parse_http_date(self.headers.get(LAST_MODIFIED)?.to_str().ok()?).ok()
+    }
+}
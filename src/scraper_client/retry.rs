@@ -0,0 +1,33 @@
+use reqwest::StatusCode;
+use std::sync::Arc;
+
+/// Decides whether a response status is worth retrying
+pub type RetryPredicate = Arc<dyn Fn(StatusCode) -> bool + Send + Sync>;
+
+/// Default classification: 408, 429 and 5xx are retryable, every other status is final
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::REQUEST_TIMEOUT | StatusCode::TOO_MANY_REQUESTS
+    ) || status.is_server_error()
+}
+
+/// Whether a transport-level error is worth retrying; requests that could not be built are not
+pub fn is_retryable_error(error: &reqwest::Error) -> bool {
+    !error.is_builder()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_retryable_status() {
+        for status in [408, 429, 500, 502, 503, 504] {
+            assert!(is_retryable_status(StatusCode::from_u16(status).unwrap()));
+        }
+        for status in [301, 400, 401, 403, 404, 410] {
+            assert!(!is_retryable_status(StatusCode::from_u16(status).unwrap()));
+        }
+    }
+}
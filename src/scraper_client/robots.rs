@@ -0,0 +1,198 @@
+use std::time::Duration;
+
+/// The rules from a robots.txt file that apply to one User-Agent
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct RobotsRules {
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+#[derive(Debug, Default)]
+struct Group {
+    agents: Vec<String>,
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+impl RobotsRules {
+    /// Parse robots.txt and keep the group for `user_agent`, falling back to the `*` group
+    ///
+    /// A group matches when its User-agent value appears in `user_agent`, ignoring case.
+    pub(crate) fn parse(robots_txt: &str, user_agent: &str) -> Self {
+        let user_agent = user_agent.to_ascii_lowercase();
+        let groups = parse_groups(robots_txt);
+
+        let specific = groups.iter().find(|group| {
+            group
+                .agents
+                .iter()
+                .any(|agent| agent != "*" && user_agent.contains(agent.as_str()))
+        });
+        let group = specific.or_else(|| {
+            groups
+                .iter()
+                .find(|group| group.agents.iter().any(|agent| agent == "*"))
+        });
+
+        match group {
+            Some(group) => Self {
+                rules: group.rules.clone(),
+                crawl_delay: group.crawl_delay,
+            },
+            None => Self::default(),
+        }
+    }
+
+    /// Whether `path` (including any query string) may be fetched
+    ///
+    /// The longest matching rule wins, with Allow winning ties; no match means allowed.
+    pub(crate) fn is_allowed(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|rule| pattern_matches(&rule.pattern, path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .is_none_or(|rule| rule.allow)
+    }
+
+    pub(crate) fn crawl_delay(&self) -> Option<Duration> {
+        self.crawl_delay
+    }
+}
+
+fn parse_groups(robots_txt: &str) -> Vec<Group> {
+    let mut groups: Vec<Group> = Vec::new();
+    let mut in_agent_lines = false;
+
+    for line in robots_txt.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let Some((field, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+
+        match field.trim().to_ascii_lowercase().as_str() {
+            "user-agent" => {
+                // Consecutive User-agent lines share one group
+                if !in_agent_lines {
+                    groups.push(Group::default());
+                }
+                if let Some(group) = groups.last_mut() {
+                    group.agents.push(value.to_ascii_lowercase());
+                }
+                in_agent_lines = true;
+            }
+            field @ ("allow" | "disallow") => {
+                in_agent_lines = false;
+                // An empty Disallow allows everything, so it adds no rule
+                if let (Some(group), false) = (groups.last_mut(), value.is_empty()) {
+                    group.rules.push(Rule {
+                        allow: field == "allow",
+                        pattern: value.to_string(),
+                    });
+                }
+            }
+            "crawl-delay" => {
+                in_agent_lines = false;
+                if let (Some(group), Ok(seconds)) = (groups.last_mut(), value.parse::<f64>()) {
+                    group.crawl_delay = Duration::try_from_secs_f64(seconds).ok();
+                }
+            }
+            _ => {}
+        }
+    }
+    groups
+}
+
+/// Match a robots.txt path pattern, supporting `*` wildcards and a trailing `$` anchor
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    if parts.is_empty() {
+        return !anchored || rest.is_empty();
+    }
+
+    for (index, part) in parts.iter().enumerate() {
+        let is_last = index == parts.len() - 1;
+        if is_last && anchored {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROBOTS: &str = "
+        # Example robots.txt
+        User-agent: BadBot
+        Disallow: /
+
+        User-agent: scraperclient
+        User-agent: OtherBot
+        Disallow: /private
+        Allow: /private/holidays
+        Crawl-delay: 1.5
+
+        User-agent: *
+        Disallow: /search
+        Disallow: /*.pdf$
+    ";
+
+    #[test]
+    fn test_robots_specific_group() {
+        let rules = RobotsRules::parse(ROBOTS, "Rust ScraperClient/1.0");
+        assert!(rules.is_allowed("/labour-relations/public-holidays"));
+        assert!(!rules.is_allowed("/private/staff"));
+        assert!(rules.is_allowed("/private/holidays/2025"));
+        assert!(rules.is_allowed("/search?q=holidays"));
+        assert_eq!(rules.crawl_delay(), Some(Duration::from_millis(1500)));
+    }
+
+    #[test]
+    fn test_robots_wildcard_group() {
+        let rules = RobotsRules::parse(ROBOTS, "curl/8.0");
+        assert!(!rules.is_allowed("/search?q=holidays"));
+        assert!(!rules.is_allowed("/docs/holidays.pdf"));
+        assert!(rules.is_allowed("/docs/holidays.pdf?download=1"));
+        assert!(rules.is_allowed("/private/staff"));
+        assert_eq!(rules.crawl_delay(), None);
+    }
+
+    #[test]
+    fn test_robots_empty_disallow_allows_everything() {
+        let rules = RobotsRules::parse("User-agent: *\nDisallow:\n", "Rust ScraperClient/1.0");
+        assert!(rules.is_allowed("/anything"));
+    }
+
+    #[test]
+    fn test_pattern_matches() {
+        assert!(pattern_matches("/a", "/a/b"));
+        assert!(pattern_matches("/a*c", "/abbbc/d"));
+        assert!(pattern_matches("/a*c$", "/abbbc"));
+        assert!(!pattern_matches("/a*c$", "/abbbcd"));
+        assert!(pattern_matches("/a$", "/a"));
+        assert!(!pattern_matches("/a$", "/ab"));
+        assert!(!pattern_matches("/b", "/a/b"));
+    }
+}
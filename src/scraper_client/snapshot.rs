@@ -0,0 +1,259 @@
+use super::FetchResponse;
+use reqwest::Url;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Longest sanitised path kept in a snapshot filename
+const MAX_NAME_LEN: usize = 100;
+
+/// Archives every fetched page as `<dir>/<host>/<path>-<timestamp>.html` plus a JSON sidecar
+///
+/// The timestamp is ISO 8601 in basic format, e.g. `20250101T093000.125Z`, so filenames for
+/// one URL sort oldest first.
+#[derive(Debug)]
+pub(crate) struct SnapshotWriter {
+    dir: PathBuf,
+    retention: Option<usize>,
+}
+
+/// What the `.json` sidecar records about a snapshot
+#[derive(Debug, Serialize)]
+struct Sidecar<'a> {
+    url: &'a str,
+    status: u16,
+    fetched_at: String,
+    headers: BTreeMap<&'a str, String>,
+}
+
+impl SnapshotWriter {
+    /// Write snapshots under `dir`, keeping only the newest `retention` per URL if set
+    pub(crate) fn new(dir: PathBuf, retention: Option<usize>) -> Self {
+        Self { dir, retention }
+    }
+
+    /// Write the body and sidecar for `response`, then prune old snapshots of the same URL
+    pub(crate) fn write(&self, response: &FetchResponse) -> io::Result<PathBuf> {
+        let url = &response.final_url;
+        let host_dir = self.dir.join(sanitise(&host_label(url)));
+        fs::create_dir_all(&host_dir)?;
+
+        let stem = path_label(url);
+        let name = format!("{}-{}", stem, timestamp(response.fetched_at));
+        let body_path = host_dir.join(format!("{}.html", name));
+        let mut headers = BTreeMap::new();
+        for (name, value) in &response.headers {
+            let value = String::from_utf8_lossy(value.as_bytes());
+            headers
+                .entry(name.as_str())
+                .and_modify(|joined: &mut String| {
+                    joined.push_str(", ");
+                    joined.push_str(&value);
+                })
+                .or_insert_with(|| value.into_owned());
+        }
+        let sidecar = Sidecar {
+            url: url.as_str(),
+            status: response.status,
+            fetched_at: humantime::format_rfc3339_millis(response.fetched_at).to_string(),
+            headers,
+        };
+
+        fs::write(&body_path, &response.body)?;
+        fs::write(
+            host_dir.join(format!("{}.json", name)),
+            serde_json::to_vec_pretty(&sidecar)?,
+        )?;
+        if let Some(retention) = self.retention {
+            prune(&host_dir, &stem, retention)?;
+        }
+        Ok(body_path)
+    }
+}
+
+/// Host and port, so servers on different ports of one machine don't share a directory
+fn host_label(url: &Url) -> String {
+    let host = url.host_str().unwrap_or("unknown-host");
+    match url.port() {
+        Some(port) => format!("{}_{}", host, port),
+        None => host.to_string(),
+    }
+}
+
+/// Path and query as a single filename-safe component, `index` for the root
+fn path_label(url: &Url) -> String {
+    let mut label = url.path().trim_matches('/').to_string();
+    if let Some(query) = url.query() {
+        label.push('_');
+        label.push_str(query);
+    }
+    if label.is_empty() {
+        label.push_str("index");
+    }
+    let mut label = sanitise(&label);
+    if label.len() > MAX_NAME_LEN {
+        label.truncate(MAX_NAME_LEN);
+    }
+    label
+}
+
+/// Replace everything but ASCII letters, digits, `.`, `-` and `_` with `_`
+fn sanitise(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    // A leading dot would hide the file and `..` would escape the directory
+    name.trim_start_matches('.').to_string()
+}
+
+/// `2025-01-01T09:30:00.125Z` without the separators, which aren't allowed in Windows filenames
+fn timestamp(time: SystemTime) -> String {
+    humantime::format_rfc3339_millis(time)
+        .to_string()
+        .replace(['-', ':'], "")
+}
+
+fn is_timestamp(s: &str) -> bool {
+    s.len() == 20 && s.as_bytes()[8] == b'T' && s.ends_with('Z')
+}
+
+/// Delete all but the newest `retention` snapshots named `<stem>-<timestamp>`
+fn prune(host_dir: &Path, stem: &str, retention: usize) -> io::Result<()> {
+    let prefix = format!("{}-", stem);
+    let mut names = Vec::new();
+    for entry in fs::read_dir(host_dir)? {
+        let file_name = entry?.file_name();
+        let Some(name) = file_name.to_str() else {
+            continue;
+        };
+        let Some(timestamp) = name
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.strip_suffix(".html"))
+        else {
+            continue;
+        };
+        if is_timestamp(timestamp) {
+            names.push(name.trim_end_matches(".html").to_string());
+        }
+    }
+
+    names.sort();
+    let excess = names.len().saturating_sub(retention);
+    for name in &names[..excess] {
+        fs::remove_file(host_dir.join(format!("{}.html", name)))?;
+        match fs::remove_file(host_dir.join(format!("{}.json", name))) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+    use std::time::Duration;
+
+    fn response(url: &str, fetched_at: SystemTime) -> FetchResponse {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/html"));
+        FetchResponse {
+            body: "<table></table>".to_string(),
+            status: 200,
+            headers,
+            final_url: Url::parse(url).unwrap(),
+            redirects: Vec::new(),
+            encoding: "UTF-8",
+            attempts: 1,
+            elapsed: Duration::ZERO,
+            fetched_at,
+            transfer: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_snapshot_names_and_sidecar() {
+        let dir = tempfile::tempdir().unwrap();
+        let writer = SnapshotWriter::new(dir.path().to_path_buf(), None);
+        let fetched_at = SystemTime::UNIX_EPOCH + Duration::from_millis(1_735_723_800_125);
+
+        let path = writer
+            .write(&response(
+                "https://www.wa.gov.au/service/public-holidays?year=2025&x=../y",
+                fetched_at,
+            ))
+            .unwrap();
+
+        assert_eq!(
+            path,
+            dir.path()
+                .join("www.wa.gov.au")
+                .join("service_public-holidays_year_2025_x_.._y-20250101T093000.125Z.html")
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "<table></table>");
+        let sidecar: serde_json::Value =
+            serde_json::from_slice(&fs::read(path.with_extension("json")).unwrap()).unwrap();
+        assert_eq!(
+            sidecar,
+            serde_json::json!({
+                "url": "https://www.wa.gov.au/service/public-holidays?year=2025&x=../y",
+                "status": 200,
+                "fetched_at": "2025-01-01T09:30:00.125Z",
+                "headers": { "content-type": "text/html" },
+            })
+        );
+    }
+
+    #[test]
+    fn test_snapshot_root_and_port_names() {
+        let url = Url::parse("http://127.0.0.1:8080/").unwrap();
+        assert_eq!(host_label(&url), "127.0.0.1_8080");
+        assert_eq!(path_label(&url), "index");
+        assert_eq!(sanitise("../../etc/passwd"), "_.._etc_passwd");
+    }
+
+    #[test]
+    fn test_snapshot_retention_keeps_newest_per_url() {
+        let dir = tempfile::tempdir().unwrap();
+        let writer = SnapshotWriter::new(dir.path().to_path_buf(), Some(2));
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_735_723_800);
+        for minute in 0..4 {
+            let fetched_at = start + Duration::from_secs(60 * minute);
+            writer
+                .write(&response("https://example.com/holidays", fetched_at))
+                .unwrap();
+        }
+        // Shares the `holidays-` prefix but is a different URL
+        writer
+            .write(&response("https://example.com/holidays-2024", start))
+            .unwrap();
+
+        let mut names: Vec<_> = fs::read_dir(dir.path().join("example.com"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "holidays-2024-20250101T093000.000Z.html",
+                "holidays-2024-20250101T093000.000Z.json",
+                "holidays-20250101T093200.000Z.html",
+                "holidays-20250101T093200.000Z.json",
+                "holidays-20250101T093300.000Z.html",
+                "holidays-20250101T093300.000Z.json",
+            ]
+        );
+    }
+}
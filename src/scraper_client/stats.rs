@@ -0,0 +1,226 @@
+use super::response::Transfer;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::time::Duration;
+
+/// How many recent durations are kept for the latency percentiles
+const MAX_LATENCY_SAMPLES: usize = 1000;
+
+/// Counters describing everything a [`super::ScraperClient`] has done since it was built or
+/// last reset
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct StatsSnapshot {
+    /// Calls that finished, whether they succeeded or not
+    pub total_requests: u64,
+    pub successful_requests: u64,
+    pub failed_requests: u64,
+    pub get_requests: u64,
+    pub post_requests: u64,
+    /// Calls dropped before finishing because another mirror answered first, see
+    /// [`super::ScraperClient::fetch_first_of`]; not part of `total_requests`
+    pub cancelled_requests: u64,
+    /// Attempts made after the first one of each call
+    pub retries: u64,
+    /// Response body bytes received for successful calls, before decompression
+    pub bytes_downloaded: u64,
+    /// The same bodies after decompression; equal to `bytes_downloaded` when nothing was compressed
+    pub decoded_bytes: u64,
+    /// Successful calls by the `Content-Encoding` their body was sent with, `identity` if none
+    pub content_encodings: BTreeMap<String, u64>,
+    /// Time spent in finished calls, including retry delays
+    pub elapsed: Duration,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    pub circuits_opened: u64,
+    /// Duration of finished calls, from the first attempt to the final outcome; `None` before
+    /// the first one
+    pub request_latency: Option<LatencySummary>,
+    /// Duration of single attempts, until the response headers or an error arrived
+    pub attempt_latency: Option<LatencySummary>,
+}
+
+/// Distribution of the most recent durations, up to 1000 of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LatencySummary {
+    /// Number of durations summarised
+    pub samples: usize,
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+}
+
+/// Counters plus the raw latency samples behind a [`StatsSnapshot`]
+#[derive(Debug, Default)]
+pub(crate) struct ScraperClientStats {
+    /// Everything except the latency summaries, which are computed on demand
+    pub(crate) totals: StatsSnapshot,
+    pub(crate) request_latency: LatencySamples,
+    pub(crate) attempt_latency: LatencySamples,
+    /// One entry per distinct host ever requested; never pruned
+    pub(crate) hosts: HashMap<String, HostStats>,
+    #[cfg(feature = "metrics")]
+    pub(crate) duration_histogram: crate::metrics::DurationHistogram,
+}
+
+impl ScraperClientStats {
+    pub(crate) fn snapshot(&self) -> StatsSnapshot {
+        StatsSnapshot {
+            request_latency: self.request_latency.summary(),
+            attempt_latency: self.attempt_latency.summary(),
+            ..self.totals.clone()
+        }
+    }
+
+    pub(crate) fn by_host(&self) -> BTreeMap<String, HostStats> {
+        self.hosts
+            .iter()
+            .map(|(host, stats)| (host.clone(), stats.clone()))
+            .collect()
+    }
+}
+
+/// Counters for the calls made to one host
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct HostStats {
+    pub requests: u64,
+    pub successes: u64,
+    pub failures: u64,
+    pub retries: u64,
+    /// Body bytes received for successful calls, before decompression
+    pub bytes_downloaded: u64,
+    /// The same bodies after decompression
+    pub decoded_bytes: u64,
+    /// Mean duration of finished calls, retry delays included
+    pub mean_latency: Duration,
+    #[serde(skip)]
+    total_latency: Duration,
+}
+
+impl HostStats {
+    pub(crate) fn record(
+        &mut self,
+        succeeded: bool,
+        retries: u64,
+        elapsed: Duration,
+        transfer: Option<&Transfer>,
+    ) {
+        self.requests += 1;
+        if succeeded {
+            self.successes += 1;
+        } else {
+            self.failures += 1;
+        }
+        self.retries += retries;
+        if let Some(transfer) = transfer {
+            self.bytes_downloaded += transfer.wire_bytes;
+            self.decoded_bytes += transfer.decoded_bytes;
+        }
+        self.total_latency += elapsed;
+        self.mean_latency = self.total_latency / self.requests as u32;
+    }
+
+    /// Share of calls that failed, from 0.0 to 1.0
+    pub fn failure_rate(&self) -> f64 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.failures as f64 / self.requests as f64
+        }
+    }
+}
+
+/// Ring buffer of the most recent durations
+#[derive(Debug, Default)]
+pub(crate) struct LatencySamples {
+    samples: VecDeque<Duration>,
+}
+
+impl LatencySamples {
+    pub(crate) fn record(&mut self, duration: Duration) {
+        if self.samples.len() == MAX_LATENCY_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(duration);
+    }
+
+    /// Min, max, mean and nearest-rank percentiles of the recorded durations
+    pub(crate) fn summary(&self) -> Option<LatencySummary> {
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        let percentile = |p: usize| sorted[(p * sorted.len()).div_ceil(100).max(1) - 1];
+        Some(LatencySummary {
+            samples: sorted.len(),
+            min: *sorted.first()?,
+            max: *sorted.last()?,
+            mean: sorted.iter().sum::<Duration>() / sorted.len() as u32,
+            p50: percentile(50),
+            p95: percentile(95),
+            p99: percentile(99),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(millis: impl IntoIterator<Item = u64>) -> LatencySamples {
+        let mut samples = LatencySamples::default();
+        for ms in millis {
+            samples.record(Duration::from_millis(ms));
+        }
+        samples
+    }
+
+    #[test]
+    fn test_host_stats_mean_latency_and_failure_rate() {
+        let mut stats = HostStats::default();
+        stats.record(true, 0, Duration::from_millis(100), None);
+        stats.record(false, 2, Duration::from_millis(300), None);
+
+        assert_eq!(stats.requests, 2);
+        assert_eq!(stats.retries, 2);
+        assert_eq!(stats.mean_latency, Duration::from_millis(200));
+        assert_eq!(stats.failure_rate(), 0.5);
+        assert_eq!(HostStats::default().failure_rate(), 0.0);
+    }
+
+    #[test]
+    fn test_latency_summary_percentiles() {
+        // 1..=100 ms in shuffled order
+        let summary = samples((1..=100).map(|ms| ms * 37 % 101))
+            .summary()
+            .unwrap();
+
+        assert_eq!(summary.samples, 100);
+        assert_eq!(summary.min, Duration::from_millis(1));
+        assert_eq!(summary.max, Duration::from_millis(100));
+        assert_eq!(summary.mean, Duration::from_micros(50_500));
+        assert_eq!(summary.p50, Duration::from_millis(50));
+        assert_eq!(summary.p95, Duration::from_millis(95));
+        assert_eq!(summary.p99, Duration::from_millis(99));
+    }
+
+    #[test]
+    fn test_latency_summary_single_sample() {
+        let summary = samples([42]).summary().unwrap();
+
+        assert_eq!(summary.min, Duration::from_millis(42));
+        assert_eq!(summary.p50, Duration::from_millis(42));
+        assert_eq!(summary.p99, Duration::from_millis(42));
+        assert!(samples([]).summary().is_none());
+    }
+
+    #[test]
+    fn test_latency_samples_keep_most_recent() {
+        let summary = samples(0..MAX_LATENCY_SAMPLES as u64 + 10)
+            .summary()
+            .unwrap();
+
+        assert_eq!(summary.samples, MAX_LATENCY_SAMPLES);
+        assert_eq!(summary.min, Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,75 @@
+use crate::errors::ScraperError;
+use reqwest::tls::Version;
+use reqwest::{Certificate, ClientBuilder};
+use std::error::Error as _;
+
+/// TLS configuration collected by the builder
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TlsSettings {
+    /// PEM-encoded certificates trusted in addition to the system roots
+    pub(crate) root_certificates: Vec<Vec<u8>>,
+    pub(crate) accept_invalid_certs: bool,
+    pub(crate) min_version: Option<Version>,
+}
+
+impl TlsSettings {
+    /// Apply the settings to a reqwest builder, failing with `Tls` on an unparseable certificate
+    pub(crate) fn apply(&self, mut builder: ClientBuilder) -> Result<ClientBuilder, ScraperError> {
+        for pem in &self.root_certificates {
+            let certificate = Certificate::from_pem(pem)
+                .map_err(|e| ScraperError::Tls(format!("invalid root certificate: {}", e)))?;
+            builder = builder.add_root_certificate(certificate);
+        }
+        if let Some(version) = self.min_version {
+            builder = builder.min_tls_version(version);
+        }
+        Ok(builder.danger_accept_invalid_certs(self.accept_invalid_certs))
+    }
+}
+
+/// Turn a transport error caused by the TLS handshake, such as an untrusted certificate,
+/// into `ScraperError::Tls`; other errors are returned unchanged
+pub(crate) fn classify(error: ScraperError) -> ScraperError {
+    let ScraperError::FetchError(e) = &error else {
+        return error;
+    };
+    if !e.is_connect() {
+        return error;
+    }
+    // reqwest doesn't expose TLS failures directly, so look for them in the source chain
+    let mut source = e.source();
+    while let Some(cause) = source {
+        let message = cause.to_string();
+        let lower = message.to_ascii_lowercase();
+        if ["certificate", "ssl", "tls", "handshake"]
+            .iter()
+            .any(|keyword| lower.contains(keyword))
+        {
+            return ScraperError::Tls(message);
+        }
+        source = cause.source();
+    }
+    error
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::Client;
+
+    #[test]
+    fn test_apply_rejects_bogus_pem() {
+        let settings = TlsSettings {
+            root_certificates: vec![b"-----BEGIN CERTIFICATE-----\nnot base64\n".to_vec()],
+            ..TlsSettings::default()
+        };
+        let result = settings.apply(Client::builder());
+        assert!(matches!(result, Err(ScraperError::Tls(_))));
+    }
+
+    #[test]
+    fn test_classify_leaves_other_errors_alone() {
+        let error = classify(ScraperError::CustomError("boom".to_string()));
+        assert!(matches!(error, ScraperError::CustomError(_)));
+    }
+}
@@ -0,0 +1,79 @@
+use rand::Rng;
+use reqwest::header::HeaderValue;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// How the client picks a User-Agent from its list for each request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UserAgentRotation {
+    /// Use the strings in order, starting again after the last one
+    #[default]
+    RoundRobin,
+    /// Pick a string at random for every request
+    Random,
+}
+
+/// Hands out User-Agent values according to a rotation strategy
+#[derive(Debug, Default)]
+pub(crate) struct UserAgentRotator {
+    user_agents: Vec<HeaderValue>,
+    rotation: UserAgentRotation,
+    next: AtomicUsize,
+}
+
+impl UserAgentRotator {
+    pub(crate) fn new(user_agents: Vec<HeaderValue>, rotation: UserAgentRotation) -> Self {
+        Self {
+            user_agents,
+            rotation,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// The User-Agent for the next request, or `None` when no rotation list is configured
+    pub(crate) fn next_user_agent(&self) -> Option<HeaderValue> {
+        if self.user_agents.is_empty() {
+            return None;
+        }
+        let index = match self.rotation {
+            UserAgentRotation::RoundRobin => {
+                self.next.fetch_add(1, Ordering::Relaxed) % self.user_agents.len()
+            }
+            UserAgentRotation::Random => rand::thread_rng().gen_range(0..self.user_agents.len()),
+        };
+        Some(self.user_agents[index].clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agents(values: &[&'static str]) -> Vec<HeaderValue> {
+        values
+            .iter()
+            .map(|value| HeaderValue::from_static(value))
+            .collect()
+    }
+
+    #[test]
+    fn test_round_robin_rotation() {
+        let rotator =
+            UserAgentRotator::new(agents(&["a", "b", "c"]), UserAgentRotation::RoundRobin);
+        let picked: Vec<HeaderValue> = (0..5).filter_map(|_| rotator.next_user_agent()).collect();
+        assert_eq!(picked, agents(&["a", "b", "c", "a", "b"]));
+    }
+
+    #[test]
+    fn test_random_rotation_stays_in_list() {
+        let list = agents(&["a", "b"]);
+        let rotator = UserAgentRotator::new(list.clone(), UserAgentRotation::Random);
+        for _ in 0..20 {
+            assert!(list.contains(&rotator.next_user_agent().unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_no_rotation_list() {
+        assert_eq!(UserAgentRotator::default().next_user_agent(), None);
+    }
+}
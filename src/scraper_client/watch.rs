@@ -0,0 +1,31 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// A page whose content differed between two consecutive polls
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentChange {
+    /// The body from the poll before the change
+    pub old_body: String,
+    /// The body that differed from it
+    pub new_body: String,
+    /// Number of fetches made, including the first one
+    pub polls: u32,
+}
+
+/// Hash of a body after normalisation, compared between polls
+pub(crate) fn body_hash(normalised: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    normalised.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_body_hash_equal_for_equal_bodies() {
+        assert_eq!(body_hash("<table></table>"), body_hash("<table></table>"));
+        assert_ne!(body_hash("<table></table>"), body_hash("<table> </table>"));
+    }
+}
@@ -0,0 +1,85 @@
+use crate::errors::ScraperError;
+use scraper::Selector;
+
+/// CSS selectors describing where a holidays table keeps its years, names and dates
+///
+/// The default matches the WA commerce page: years in `<thead>` header cells and each
+/// holiday's name in a `<strong>` inside the row's `<th>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorConfig {
+    /// The tables to read
+    pub table: String,
+    /// Year header cells within a table; the first is the name column's header and is skipped
+    pub year_header: String,
+    /// Holiday rows within a table
+    pub row: String,
+    /// The element holding the holiday name within a row
+    pub name_cell: String,
+    /// Date cells within a row, one per year column unless they have a `colspan`
+    pub date_cell: String,
+}
+
+impl Default for SelectorConfig {
+    fn default() -> Self {
+        Self {
+            table: "table".to_string(),
+            year_header: "thead th".to_string(),
+            row: "tbody tr".to_string(),
+            name_cell: "th strong".to_string(),
+            date_cell: "td".to_string(),
+        }
+    }
+}
+
+/// A [`SelectorConfig`] with every selector parsed
+pub(crate) struct Selectors {
+    pub(crate) table: Selector,
+    pub(crate) year_header: Selector,
+    pub(crate) row: Selector,
+    pub(crate) name_cell: Selector,
+    pub(crate) date_cell: Selector,
+}
+
+impl SelectorConfig {
+    /// Parse every selector, naming the field of the first one that is invalid
+    pub(crate) fn parse(&self) -> Result<Selectors, ScraperError> {
+        Ok(Selectors {
+            table: parse_field("table", &self.table)?,
+            year_header: parse_field("year_header", &self.year_header)?,
+            row: parse_field("row", &self.row)?,
+            name_cell: parse_field("name_cell", &self.name_cell)?,
+            date_cell: parse_field("date_cell", &self.date_cell)?,
+        })
+    }
+}
+
+fn parse_field(field: &str, selector: &str) -> Result<Selector, ScraperError> {
+    Selector::parse(selector).map_err(|err| {
+        ScraperError::SelectorError(format!("{} selector {:?}: {}", field, selector, err))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_selector_names_field() {
+        let config = SelectorConfig {
+            date_cell: "td[".to_string(),
+            ..SelectorConfig::default()
+        };
+
+        match config.parse() {
+            Err(ScraperError::SelectorError(message)) => {
+                assert!(
+                    message.starts_with(r#"date_cell selector "td[": "#),
+                    "{}",
+                    message
+                )
+            }
+            Err(other) => panic!("Unexpected error: {:?}", other),
+            Ok(_) => panic!("Selector should not parse"),
+        }
+    }
+}
@@ -0,0 +1,102 @@
+mod support;
+
+use rusqlite::Connection;
+use rust_assignment::holiday_processor::HolidayProcessor;
+use rust_assignment::scraper_client::{FetchOutcome, ScraperClient};
+use std::time::Duration;
+
+/// Fetch `route` from the fixture server, parse it and save it into a fresh database
+async fn scrape_into_db(route: &str) -> (Connection, wiremock::MockServer) {
+    let server = support::start_fixture_server().await;
+    let client = ScraperClient::builder()
+        .retry_delay(Duration::from_millis(10))
+        .build()
+        .expect("Failed to build client");
+
+    let raw_html = client
+        .fetch_url(format!("{}{}", server.uri(), route).as_str())
+        .await
+        .expect("Fetch failed");
+
+    let mut processor = HolidayProcessor::new(raw_html);
+    processor.run().await.expect("Processor failed");
+
+    let conn = Connection::open_in_memory().expect("Failed to open database");
+    processor.save_to_db(&conn).await.expect("Save failed");
+    (conn, server)
+}
+
+fn saved_holidays(conn: &Connection) -> Vec<(i32, String, String)> {
+    let mut stmt = conn
+        .prepare("SELECT year, name, date FROM holidays ORDER BY id")
+        .unwrap();
+    stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_e2e_fetch_parse_save() {
+    let (conn, server) = scrape_into_db("/holidays").await;
+
+    let holidays = saved_holidays(&conn);
+    assert_eq!(holidays.len(), 20);
+    assert_eq!(
+        holidays[0],
+        (
+            2024,
+            "New Year's Day".to_string(),
+            "Monday 1 January".to_string()
+        )
+    );
+    assert_eq!(
+        holidays[19],
+        (
+            2025,
+            "Boxing Day".to_string(),
+            "Friday 26 December".to_string()
+        )
+    );
+    assert_eq!(support::request_count(&server, "/holidays").await, 1);
+}
+
+#[tokio::test]
+async fn test_e2e_retries_until_success() {
+    let (conn, server) = scrape_into_db("/flaky").await;
+
+    assert_eq!(saved_holidays(&conn).len(), 20);
+    assert_eq!(support::request_count(&server, "/flaky").await, 3);
+}
+
+#[tokio::test]
+async fn test_e2e_revalidates_unchanged_page() {
+    let server = support::start_fixture_server().await;
+    let client = ScraperClient::builder()
+        .retry_delay(Duration::from_millis(10))
+        .build()
+        .expect("Failed to build client");
+    let url = format!("{}/cached", server.uri());
+
+    let first = client
+        .fetch_url_cached(url.as_str())
+        .await
+        .expect("Fetch failed");
+    let second = client
+        .fetch_url_cached(url.as_str())
+        .await
+        .expect("Fetch failed");
+
+    assert_eq!(first, FetchOutcome::Fresh(support::WA_FIXTURE.to_string()));
+    assert_eq!(
+        second,
+        FetchOutcome::NotModified(support::WA_FIXTURE.to_string())
+    );
+
+    let mut processor = HolidayProcessor::new(second.into_body());
+    processor.run().await.expect("Processor failed");
+    let conn = Connection::open_in_memory().expect("Failed to open database");
+    processor.save_to_db(&conn).await.expect("Save failed");
+    assert_eq!(saved_holidays(&conn).len(), 20);
+    assert_eq!(support::request_count(&server, "/cached").await, 2);
+}
@@ -0,0 +1,67 @@
+//! Local HTTP server shared by the integration tests so they never touch the network
+
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// The bundled copy of the WA public holidays page
+pub const WA_FIXTURE: &str = include_str!("../fixtures/wa_public_holidays.html");
+
+/// ETag the fixture is served with at `/cached`
+pub const FIXTURE_ETAG: &str = "\"wa-2024-2025\"";
+
+/// Serves the WA fixture at `/holidays`; at `/flaky`, which fails with a 500 twice before
+/// succeeding; and at `/cached`, which answers 304 when the request carries [`FIXTURE_ETAG`]
+pub async fn start_fixture_server() -> MockServer {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/holidays"))
+        .respond_with(html_response(WA_FIXTURE))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/flaky"))
+        .respond_with(ResponseTemplate::new(500))
+        .up_to_n_times(2)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/flaky"))
+        .respond_with(html_response(WA_FIXTURE))
+        .with_priority(2)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/cached"))
+        .and(header("if-none-match", FIXTURE_ETAG))
+        .respond_with(ResponseTemplate::new(304))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/cached"))
+        .respond_with(html_response(WA_FIXTURE).insert_header("etag", FIXTURE_ETAG))
+        .with_priority(2)
+        .mount(&server)
+        .await;
+
+    server
+}
+
+/// Number of requests the server has received for `route`
+pub async fn request_count(server: &MockServer, route: &str) -> usize {
+    server
+        .received_requests()
+        .await
+        .unwrap_or_default()
+        .iter()
+        .filter(|request| request.url.path() == route)
+        .count()
+}
+
+fn html_response(body: &str) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_raw(body, "text/html; charset=utf-8")
+}